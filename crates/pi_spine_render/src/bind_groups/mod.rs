@@ -1,149 +1,226 @@
-use std::{hash::{Hash, Hasher}, num::NonZeroU64};
-
-use pi_assets::{asset::Handle, mgr::AssetMgr};
-use pi_hash::DefaultHasher;
-use pi_render::{rhi::{bind_group::BindGroup, asset::{RenderRes, TextureRes}, sampler::SamplerDesc, device::RenderDevice}, renderer::sampler::SamplerRes, asset::ASSET_SIZE_FOR_UNKOWN};
-
-use pi_share::Share;
-
-use crate::{shaders::{KeySpineShader, SingleSpineBindGroupLayout}, binds::param::{SpineBindBuffer, BindParam}};
-
-
-pub struct UsedBindGroupSet0 {
-    pub bindgroup: Handle<RenderRes<BindGroup>>,
-    pub offsets: [wgpu::DynamicOffset;1],
-}
-
-pub struct BindGroupSet1 {
-    pub bindgroup: Handle<RenderRes<BindGroup>>,
-}
-
-pub struct UsedBindGroupSet1 {
-    pub bindgroup: Handle<RenderRes<BindGroup>>,
-}
-
-#[derive(Debug, Clone)]
-pub struct KeySpineBindGroup {
-    url: Option<u64>,
-    buffer: Handle<SpineBindBuffer>,
-    sampler: Option<SamplerDesc>,
-}
-impl KeySpineBindGroup {
-    fn to_u64(&self) -> u64 {
-        let mut hasher = DefaultHasher::default();
-        self.hash(&mut hasher);
-        hasher.finish()
-    }
-}
-impl Hash for KeySpineBindGroup {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.url.hash(state);
-        self.buffer.key().hash(state);
-        self.sampler.hash(state);
-    }
-}
-impl PartialEq for KeySpineBindGroup {
-    fn eq(&self, other: &Self) -> bool {
-        self.url == other.url && self.buffer.key() == other.buffer.key() && self.sampler == other.sampler
-    }
-}
-impl Eq for KeySpineBindGroup {}
-
-pub struct SpineBindGroup {
-    pub(crate) bindgroup: Handle<RenderRes<BindGroup>>,
-    texture: Option<Handle<TextureRes>>,
-    sampler: Option<Handle<SamplerRes>>,
-    param: Handle<SpineBindBuffer>,
-}
-impl SpineBindGroup {
-    pub fn colored(
-        param: Handle<SpineBindBuffer>,
-        device: &RenderDevice,
-        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
-        bind_group_layouts: &SingleSpineBindGroupLayout,
-    ) -> Self {
-        let _key_layout = KeySpineShader::Colored;
-
-        let key = KeySpineBindGroup {
-            url: None,
-            buffer: param.clone(),
-            sampler: None,
-        };
-        let key_u64 = key.to_u64();
-
-        let bindgroup = if let Some(bindgroup) = asset_mgr.get(&key_u64) {
-            bindgroup
-        } else {
-            let bindgroup = device.create_bind_group(
-                &wgpu::BindGroupDescriptor {
-                    label: None,
-                    layout: &bind_group_layouts.colored,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding { buffer: param.buffer(), offset: 0, size: NonZeroU64::new(BindParam::SIZE as u64) } ),
-                        }
-                    ],
-                }
-            );
-
-            asset_mgr.insert(key_u64, RenderRes::new(bindgroup, ASSET_SIZE_FOR_UNKOWN)).unwrap()
-        };
-
-        Self { bindgroup, texture: None, sampler: None, param }
-    }
-    pub fn colored_textured(
-        param: Handle<SpineBindBuffer>,
-        device: &RenderDevice,
-        texture: Handle<TextureRes>,
-        sampler: Handle<SamplerRes>,
-        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
-        bind_group_layouts: &SingleSpineBindGroupLayout,
-    ) -> Self {
-        let key = KeySpineBindGroup {
-            url: Some(texture.key().clone()),
-            buffer: param.clone(),
-            sampler: Some(sampler.key().clone()),
-        };
-        let key_u64 = key.to_u64();
-
-        let bindgroup = if let Some(bindgroup) = asset_mgr.get(&key_u64) {
-            bindgroup
-        } else {
-            let bindgroup = device.create_bind_group(
-                &wgpu::BindGroupDescriptor {
-                    label: None,
-                    layout: &bind_group_layouts.colored_textured,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding { buffer: param.buffer(), offset: 0, size: NonZeroU64::new(BindParam::SIZE as u64) } ),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::TextureView(&texture.texture_view),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 2,
-                            resource: wgpu::BindingResource::Sampler(&sampler.0),
-                        },
-                    ],
-                }
-            );
-
-            asset_mgr.insert(key_u64, RenderRes::new(bindgroup, ASSET_SIZE_FOR_UNKOWN)).unwrap()
-        };
-
-        Self { bindgroup, texture: Some(texture), sampler: Some(sampler), param }
-    }
-    pub fn two_colored_textured(
-        param: Handle<SpineBindBuffer>,
-        device: &RenderDevice,
-        texture: Handle<TextureRes>,
-        sampler: Handle<SamplerRes>,
-        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
-        bind_group_layouts: &SingleSpineBindGroupLayout,
-    ) -> Self {
-        Self::colored_textured(param, device, texture, sampler, asset_mgr, bind_group_layouts)
-    }
-}
\ No newline at end of file
+use std::{hash::{Hash, Hasher}, num::NonZeroU64};
+
+use pi_assets::{asset::Handle, mgr::AssetMgr};
+use pi_hash::DefaultHasher;
+use pi_render::{rhi::{bind_group::BindGroup, bind_group_layout::BindGroupLayout, asset::{RenderRes, TextureRes}, sampler::SamplerDesc, device::RenderDevice}, renderer::sampler::SamplerRes, asset::ASSET_SIZE_FOR_UNKOWN};
+
+use pi_share::Share;
+
+use crate::{shaders::{KeySpineShader, SingleSpineBindGroupLayout}, binds::param::{SpineUniformRing, BindParam, BindBones}};
+
+
+#[derive(Clone)]
+pub struct UsedBindGroupSet0 {
+    pub bindgroup: Handle<RenderRes<BindGroup>>,
+    pub offsets: [wgpu::DynamicOffset;1],
+}
+
+pub struct BindGroupSet1 {
+    pub bindgroup: Handle<RenderRes<BindGroup>>,
+}
+
+#[derive(Clone)]
+pub struct UsedBindGroupSet1 {
+    pub bindgroup: Handle<RenderRes<BindGroup>>,
+    pub offsets: [wgpu::DynamicOffset;1],
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct KeySpineBindGroup {
+    url: Option<u64>,
+    /// Uniform 环形 Buffer 的版本, Buffer 重建后需要新的 BindGroup
+    buffer: u64,
+    sampler: Option<SamplerDesc>,
+    /// 纹理为 D2Array 视图, 与 D2 视图的 BindGroup 布局不同
+    array: bool,
+    /// 所属的 set
+    set: u32,
+}
+impl KeySpineBindGroup {
+    fn to_u64(&self) -> u64 {
+        let mut hasher = DefaultHasher::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+pub struct SpineBindGroup {
+    pub(crate) bindgroup: UsedBindGroupSet0,
+    texture: Option<Handle<TextureRes>>,
+    sampler: Option<Handle<SamplerRes>>,
+    /// 是否新建 (未命中缓存)
+    pub(crate) created: bool,
+}
+impl SpineBindGroup {
+    pub fn colored(
+        param: &SpineUniformRing,
+        offset: wgpu::DynamicOffset,
+        device: &RenderDevice,
+        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
+        bind_group_layouts: &SingleSpineBindGroupLayout,
+    ) -> Option<Self> {
+        let _key_layout = KeySpineShader::Colored;
+        let buffer = param.buffer()?;
+
+        let key = KeySpineBindGroup {
+            url: None,
+            buffer: param.generation(),
+            sampler: None,
+            array: false,
+            set: 0,
+        };
+        let key_u64 = key.to_u64();
+
+        let mut created = false;
+        let bindgroup = if let Some(bindgroup) = asset_mgr.get(&key_u64) {
+            bindgroup
+        } else {
+            created = true;
+            let bindgroup = device.create_bind_group(
+                &wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: &bind_group_layouts.colored,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding { buffer, offset: 0, size: NonZeroU64::new(BindParam::SIZE as u64) } ),
+                        }
+                    ],
+                }
+            );
+
+            asset_mgr.insert(key_u64, RenderRes::new(bindgroup, ASSET_SIZE_FOR_UNKOWN)).ok()?
+        };
+
+        Some(Self { bindgroup: UsedBindGroupSet0 { bindgroup, offsets: [offset] }, texture: None, sampler: None, created })
+    }
+    pub fn colored_textured(
+        param: &SpineUniformRing,
+        offset: wgpu::DynamicOffset,
+        device: &RenderDevice,
+        texture: Handle<TextureRes>,
+        sampler: Handle<SamplerRes>,
+        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
+        bind_group_layouts: &SingleSpineBindGroupLayout,
+    ) -> Option<Self> {
+        Self::textured(param, offset, device, (texture, sampler), asset_mgr, bind_group_layouts, false)
+    }
+    /// 纹理为 D2Array 视图
+    pub fn two_colored_textured_array(
+        param: &SpineUniformRing,
+        offset: wgpu::DynamicOffset,
+        device: &RenderDevice,
+        texture: Handle<TextureRes>,
+        sampler: Handle<SamplerRes>,
+        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
+        bind_group_layouts: &SingleSpineBindGroupLayout,
+    ) -> Option<Self> {
+        Self::textured(param, offset, device, (texture, sampler), asset_mgr, bind_group_layouts, true)
+    }
+    /// array 为 true 时纹理为 D2Array 视图
+    fn textured(
+        param: &SpineUniformRing,
+        offset: wgpu::DynamicOffset,
+        device: &RenderDevice,
+        (texture, sampler): (Handle<TextureRes>, Handle<SamplerRes>),
+        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
+        bind_group_layouts: &SingleSpineBindGroupLayout,
+        array: bool,
+    ) -> Option<Self> {
+        let buffer = param.buffer()?;
+        let layout: &BindGroupLayout = if array { &bind_group_layouts.two_colored_textured_array } else { &bind_group_layouts.colored_textured };
+
+        let key = KeySpineBindGroup {
+            url: Some(texture.key().clone()),
+            buffer: param.generation(),
+            sampler: Some(sampler.key().clone()),
+            array,
+            set: 0,
+        };
+        let key_u64 = key.to_u64();
+
+        let mut created = false;
+        let bindgroup = if let Some(bindgroup) = asset_mgr.get(&key_u64) {
+            bindgroup
+        } else {
+            created = true;
+            let bindgroup = device.create_bind_group(
+                &wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding { buffer, offset: 0, size: NonZeroU64::new(BindParam::SIZE as u64) } ),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(&texture.texture_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::Sampler(&sampler.0),
+                        },
+                    ],
+                }
+            );
+
+            asset_mgr.insert(key_u64, RenderRes::new(bindgroup, ASSET_SIZE_FOR_UNKOWN)).ok()?
+        };
+
+        Some(Self { bindgroup: UsedBindGroupSet0 { bindgroup, offsets: [offset] }, texture: Some(texture), sampler: Some(sampler), created })
+    }
+    pub fn two_colored_textured(
+        param: &SpineUniformRing,
+        offset: wgpu::DynamicOffset,
+        device: &RenderDevice,
+        texture: Handle<TextureRes>,
+        sampler: Handle<SamplerRes>,
+        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
+        bind_group_layouts: &SingleSpineBindGroupLayout,
+    ) -> Option<Self> {
+        Self::colored_textured(param, offset, device, texture, sampler, asset_mgr, bind_group_layouts)
+    }
+    /// 蒙皮骨骼矩阵 (set 1), 返回 BindGroup 及是否新建
+    pub fn bones(
+        bones: &SpineUniformRing,
+        offset: wgpu::DynamicOffset,
+        device: &RenderDevice,
+        asset_mgr: &Share<AssetMgr<RenderRes<BindGroup>>>,
+        bind_group_layouts: &SingleSpineBindGroupLayout,
+    ) -> Option<(UsedBindGroupSet1, bool)> {
+        let buffer = bones.buffer()?;
+
+        let key = KeySpineBindGroup {
+            url: None,
+            buffer: bones.generation(),
+            sampler: None,
+            array: false,
+            set: 1,
+        };
+        let key_u64 = key.to_u64();
+
+        let mut created = false;
+        let bindgroup = if let Some(bindgroup) = asset_mgr.get(&key_u64) {
+            bindgroup
+        } else {
+            created = true;
+            let bindgroup = device.create_bind_group(
+                &wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: &bind_group_layouts.bones,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding { buffer, offset: 0, size: NonZeroU64::new(BindBones::SIZE as u64) } ),
+                        }
+                    ],
+                }
+            );
+
+            asset_mgr.insert(key_u64, RenderRes::new(bindgroup, ASSET_SIZE_FOR_UNKOWN)).ok()?
+        };
+
+        Some((UsedBindGroupSet1 { bindgroup, offsets: [offset] }, created))
+    }
+}
@@ -1,2 +1,2 @@
-
-pub mod param;
+pub mod param;
+pub mod uniform;
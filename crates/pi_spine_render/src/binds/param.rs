@@ -1,108 +1,111 @@
-use std::{num::NonZeroU64, sync::Arc, fmt::Debug};
-
-use pi_assets::{asset::{Handle, Asset, GarbageEmpty, Size}, mgr::AssetMgr};
-use pi_render::rhi::{device::RenderDevice, BufferInitDescriptor, RenderQueue, buffer::Buffer};
-use pi_share::{Share, ShareMutex};
-
-pub struct BindBufferAllocator {
-    asset_mgr: Share<AssetMgr<SpineBindBuffer>>,
-    couter: usize,
-    list: Arc<Vec<usize>>,
-    mutex: ShareMutex<()>,
-}
-impl BindBufferAllocator {
-    pub fn new(capacity: usize, timeout: usize) -> Self {
-        Self {
-            asset_mgr: AssetMgr::<SpineBindBuffer>::new(GarbageEmpty(), false, capacity, timeout),
-            couter: 0,
-            list: Arc::new(vec![]),
-            mutex: ShareMutex::new(())
-        }
-    }
-    pub fn allocate(&mut self, device: &RenderDevice, queue: &RenderQueue, data: &[u8]) -> Option<SpineBindBufferUsage> {
-        let list = unsafe {
-            &mut *(Arc::as_ptr(&self.list) as usize as *mut Vec<usize>)
-        };
-        let idx = if let Some(idx) = list.pop() {
-            idx
-        } else {
-            self.couter += 1;
-            self.couter
-        };
-
-        if let Some(buffer) = self.asset_mgr.get(&idx) {
-            queue.write_buffer(&buffer.buffer, 0, data);
-            Some(SpineBindBufferUsage(buffer))
-        } else {
-            let buffer = device.create_buffer_with_data(
-                &BufferInitDescriptor {
-                    label: None,
-                    contents: &[0;BindParam::SIZE],
-                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
-                }
-            );
-            let buffer = SpineBindBuffer {
-                buffer,
-                idx,
-                list: self.list.clone(),
-            };
-            queue.write_buffer(&buffer.buffer, 0, data);
-            if let Ok(buffer) = self.asset_mgr.insert(idx, buffer) {
-                Some(SpineBindBufferUsage(buffer))
-            } else {
-                None
-            }
-        }
-    }
-}
-
-pub struct SpineBindBuffer {
-    buffer: Buffer,
-    idx: usize,
-    list: Arc<Vec<usize>>,
-}
-impl SpineBindBuffer {
-    pub fn buffer(&self) -> &wgpu::Buffer {
-        &self.buffer
-    }
-}
-impl Debug for SpineBindBuffer {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("SpineBindBuffer").field("idx", &self.idx).finish()
-    }
-}
-impl Asset for SpineBindBuffer {
-    type Key = usize;
-}
-
-impl Size for SpineBindBuffer {
-    fn size(&self) -> usize {
-        BindParam::SIZE
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct SpineBindBufferUsage(pub Handle<SpineBindBuffer>);
-impl Drop for SpineBindBufferUsage {
-    fn drop(&mut self) {
-        let list = unsafe {
-            &mut *(Arc::as_ptr(&self.0.list) as usize as *mut Vec<usize>)
-        };
-        list.push(self.0.idx)
-    }
-}
-
-pub struct BindParam {
-    pub(crate) buffer: Handle<SpineBindBuffer>,
-}
-impl BindParam {
-    pub const SIZE: usize = (16 + 4 + 4) * 4;
-    pub fn layout_entry() -> wgpu::BindGroupLayoutEntry {
-        wgpu::BindGroupLayoutEntry {
-            binding: 0,
-            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-            ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: NonZeroU64::new(Self::SIZE as u64) },
-            count: None,
-        }
-    }
-}
\ No newline at end of file
+use std::num::NonZeroU64;
+
+use pi_render::rhi::{device::RenderDevice, BufferInitDescriptor, RenderQueue, buffer::Buffer};
+
+/// 每帧一个 Uniform 环形 Buffer, 每次绘制的参数以动态偏移访问
+pub struct SpineUniformRing {
+    buffer: Option<Buffer>,
+    /// Buffer 的字节数
+    capacity: u32,
+    /// 每次重建 Buffer 时递增, 用于区分 BindGroup
+    generation: u64,
+    /// 单个参数块字节数 (BindGroup 绑定大小)
+    size: u32,
+    data: Vec<u8>,
+}
+impl SpineUniformRing {
+    /// 动态偏移对齐, 不小于任何设备的 min_uniform_buffer_offset_alignment
+    pub const ALIGNMENT: u32 = 256;
+    pub fn new(size: u32, capacity: u32) -> Self {
+        Self {
+            buffer: None,
+            capacity: Self::align(capacity.max(size)),
+            generation: 0,
+            size,
+            data: vec![],
+        }
+    }
+    fn align(size: u32) -> u32 {
+        (size + Self::ALIGNMENT - 1) / Self::ALIGNMENT * Self::ALIGNMENT
+    }
+    /// 写入一个参数块, 返回其动态偏移
+    pub fn push(&mut self, data: &[u8]) -> wgpu::DynamicOffset {
+        let offset = self.data.len();
+        let size = self.size as usize;
+        self.data.extend_from_slice(&data[0..data.len().min(size)]);
+        self.data.resize(offset + Self::align(self.size) as usize, 0);
+        offset as wgpu::DynamicOffset
+    }
+    /// 本帧参数写入完成后, 保证 Buffer 足够大; 创建绑定前调用
+    pub fn prepare(&mut self, device: &RenderDevice) {
+        let used = self.data.len() as u32;
+        if self.buffer.is_none() || used > self.capacity {
+            while self.capacity < used {
+                self.capacity *= 2;
+            }
+            let buffer = device.create_buffer_with_data(
+                &BufferInitDescriptor {
+                    label: Some("SpineUniformRing"),
+                    contents: &vec![0; self.capacity as usize],
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+                }
+            );
+            self.buffer = Some(buffer);
+            self.generation += 1;
+        }
+    }
+    pub fn upload(&mut self, queue: &RenderQueue) {
+        if let Some(buffer) = &self.buffer {
+            if self.data.len() > 0 {
+                queue.write_buffer(buffer, 0, &self.data);
+            }
+        }
+        self.data.clear();
+    }
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+    pub fn used(&self) -> u32 {
+        self.data.len() as u32
+    }
+}
+
+/// 蒙皮骨骼矩阵块, 位于 set 1, 以动态偏移访问
+pub struct BindBones;
+impl BindBones {
+    /// 单次绘制的最大骨骼数, 与 skinned.vert 中 u_bones 的长度一致
+    pub const MAX: usize = 128;
+    /// 每个骨骼 2 个 vec4
+    pub const FLOATS_PER_BONE: usize = 8;
+    pub const SIZE: usize = Self::MAX * Self::FLOATS_PER_BONE * 4;
+    pub fn layout_entry() -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: true, min_binding_size: NonZeroU64::new(Self::SIZE as u64) },
+            count: None,
+        }
+    }
+}
+
+pub struct BindParam;
+impl BindParam {
+    pub const SIZE: usize = (16 + 4 + 4) * 4;
+    pub fn layout_entry() -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: true, min_binding_size: NonZeroU64::new(Self::SIZE as u64) },
+            count: None,
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt::Display;
+
+use crate::binds::param::BindParam;
+
+/// u_maskflag.w 的模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ESpineMaskMode {
+    None = 0,
+    /// 以 u_maskflag.rgb 纯色覆盖
+    Color = 1,
+    /// u_maskflag.rgb 为色相/饱和度/明度调整
+    HsvAdjust = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ESpineUniformError {
+    /// 数据长度与 Param 块不符
+    Length { expect: usize, actual: usize },
+    /// 存在 NaN 或无穷
+    NonFinite { index: usize },
+    /// u_maskflag.w 不是有效模式
+    MaskMode(f32),
+    /// 饱和度/明度调整超出 [-1, 1]
+    HsvRange(f32, f32),
+}
+impl Display for ESpineUniformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Length { expect, actual } => write!(f, "SpineUniform expect {} floats, got {}", expect, actual),
+            Self::NonFinite { index } => write!(f, "SpineUniform non-finite value at {}", index),
+            Self::MaskMode(mode) => write!(f, "SpineUniform invalid mask mode {}", mode),
+            Self::HsvRange(s, v) => write!(f, "SpineUniform saturation/value out of [-1, 1]: {}, {}", s, v),
+        }
+    }
+}
+impl std::error::Error for ESpineUniformError {}
+
+/// 与着色器中 Param 块布局一致
+/// ```glsl
+/// layout(set = 0, binding = 0) uniform Param {
+///     mat4 u_projTrans;
+///     vec4 u_maskflag;
+///     vec4 u_visibility;
+/// };
+/// ```
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpineUniform {
+    pub proj_trans: [f32; 16],
+    pub maskflag: [f32; 4],
+    /// x: rgb 缩放, y: rgb 是否乘 alpha, z: alpha 缩放
+    pub visibility: [f32; 4],
+}
+impl Default for SpineUniform {
+    fn default() -> Self {
+        Self {
+            proj_trans: [
+                1., 0., 0., 0.,
+                0., 1., 0., 0.,
+                0., 0., 1., 0.,
+                0., 0., 0., 1.,
+            ],
+            maskflag: [0., 0., 0., ESpineMaskMode::None as u8 as f32],
+            visibility: [1., 0., 1., 0.],
+        }
+    }
+}
+impl SpineUniform {
+    pub const FLOATS: usize = BindParam::SIZE / 4;
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// 列主序投影矩阵
+    pub fn with_projection(mut self, proj_trans: &[f32; 16]) -> Self {
+        self.proj_trans = *proj_trans;
+        self
+    }
+    pub fn with_mask_none(mut self) -> Self {
+        self.maskflag = [0., 0., 0., ESpineMaskMode::None as u8 as f32];
+        self
+    }
+    /// 以纯色覆盖 (保留 alpha)
+    pub fn with_mask_color(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.maskflag = [r, g, b, ESpineMaskMode::Color as u8 as f32];
+        self
+    }
+    /// 色相偏移 (0-1 为一周), 饱和度与明度调整范围 [-1, 1]
+    pub fn with_hsv_adjust(mut self, hue: f32, saturation: f32, value: f32) -> Result<Self, ESpineUniformError> {
+        if !(-1.0..=1.0).contains(&saturation) || !(-1.0..=1.0).contains(&value) {
+            return Err(ESpineUniformError::HsvRange(saturation, value));
+        }
+        self.maskflag = [hue, saturation, value, ESpineMaskMode::HsvAdjust as u8 as f32];
+        Ok(self)
+    }
+    /// rgb 与 alpha 的缩放, 用于淡入淡出
+    pub fn with_visibility(mut self, rgb: f32, alpha: f32) -> Self {
+        self.visibility[0] = rgb;
+        self.visibility[2] = alpha;
+        self
+    }
+    /// 输出 rgb 是否再乘 alpha (配合预乘混合)
+    pub fn with_premultiplied_alpha(mut self, flag: bool) -> Self {
+        self.visibility[1] = if flag { 1. } else { 0. };
+        self
+    }
+    pub fn mask_mode(&self) -> Option<ESpineMaskMode> {
+        match self.maskflag[3] {
+            v if v == ESpineMaskMode::None as u8 as f32 => Some(ESpineMaskMode::None),
+            v if v == ESpineMaskMode::Color as u8 as f32 => Some(ESpineMaskMode::Color),
+            v if v == ESpineMaskMode::HsvAdjust as u8 as f32 => Some(ESpineMaskMode::HsvAdjust),
+            _ => None,
+        }
+    }
+    pub fn validate(&self) -> Result<(), ESpineUniformError> {
+        if let Some(index) = self.as_slice().iter().position(|v| !v.is_finite()) {
+            return Err(ESpineUniformError::NonFinite { index });
+        }
+        match self.mask_mode() {
+            Some(ESpineMaskMode::HsvAdjust) => {
+                let (s, v) = (self.maskflag[1], self.maskflag[2]);
+                if !(-1.0..=1.0).contains(&s) || !(-1.0..=1.0).contains(&v) {
+                    return Err(ESpineUniformError::HsvRange(s, v));
+                }
+            },
+            Some(_) => {},
+            None => return Err(ESpineUniformError::MaskMode(self.maskflag[3])),
+        }
+        Ok(())
+    }
+    /// 从旧接口的 24 个 f32 构建
+    pub fn from_slice(value: &[f32]) -> Result<Self, ESpineUniformError> {
+        if value.len() != Self::FLOATS {
+            return Err(ESpineUniformError::Length { expect: Self::FLOATS, actual: value.len() });
+        }
+        let result: Self = *bytemuck::from_bytes(bytemuck::cast_slice(value));
+        result.validate()?;
+        Ok(result)
+    }
+    pub fn as_slice(&self) -> &[f32] {
+        bytemuck::cast_slice(bytemuck::bytes_of(self))
+    }
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ESpineMaskMode, ESpineUniformError, SpineUniform};
+
+    #[test]
+    fn default_is_valid() {
+        let uniform = SpineUniform::new();
+        assert_eq!(uniform.validate(), Ok(()));
+        assert_eq!(uniform.as_slice().len(), SpineUniform::FLOATS);
+        assert_eq!(SpineUniform::from_slice(uniform.as_slice()), Ok(uniform));
+    }
+
+    #[test]
+    fn from_slice_checks_length() {
+        let value = [0.; 23];
+        assert_eq!(SpineUniform::from_slice(&value), Err(ESpineUniformError::Length { expect: SpineUniform::FLOATS, actual: 23 }));
+        let value = [0.; 25];
+        assert_eq!(SpineUniform::from_slice(&value), Err(ESpineUniformError::Length { expect: SpineUniform::FLOATS, actual: 25 }));
+    }
+
+    #[test]
+    fn non_finite_reports_index() {
+        let mut uniform = SpineUniform::new();
+        uniform.proj_trans[5] = f32::NAN;
+        assert_eq!(uniform.validate(), Err(ESpineUniformError::NonFinite { index: 5 }));
+
+        let mut value = SpineUniform::new().as_slice().to_vec();
+        value[21] = f32::INFINITY;
+        assert_eq!(SpineUniform::from_slice(&value), Err(ESpineUniformError::NonFinite { index: 21 }));
+    }
+
+    #[test]
+    fn invalid_mask_mode() {
+        let mut uniform = SpineUniform::new();
+        uniform.maskflag[3] = 3.;
+        assert_eq!(uniform.mask_mode(), None);
+        assert_eq!(uniform.validate(), Err(ESpineUniformError::MaskMode(3.)));
+
+        uniform.maskflag[3] = 1.5;
+        assert_eq!(uniform.validate(), Err(ESpineUniformError::MaskMode(1.5)));
+    }
+
+    #[test]
+    fn hsv_range() {
+        assert_eq!(SpineUniform::new().with_hsv_adjust(0.5, 1.5, 0.).err(), Some(ESpineUniformError::HsvRange(1.5, 0.)));
+        assert_eq!(SpineUniform::new().with_hsv_adjust(0.5, 0., -2.).err(), Some(ESpineUniformError::HsvRange(0., -2.)));
+
+        let uniform = SpineUniform::new().with_hsv_adjust(0.5, -1., 1.).unwrap();
+        assert_eq!(uniform.mask_mode(), Some(ESpineMaskMode::HsvAdjust));
+        assert_eq!(uniform.validate(), Ok(()));
+
+        // 直接改写的数据同样在 validate 中检查
+        let mut value = uniform.as_slice().to_vec();
+        value[17] = 1.25;
+        assert_eq!(SpineUniform::from_slice(&value), Err(ESpineUniformError::HsvRange(1.25, 1.)));
+    }
+
+    #[test]
+    fn color_mask_allows_any_rgb() {
+        let uniform = SpineUniform::new().with_mask_color(2., -1., 0.5);
+        assert_eq!(uniform.mask_mode(), Some(ESpineMaskMode::Color));
+        assert_eq!(uniform.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use pi_hash::XHashMap;
+use pi_render::{rhi::{device::RenderDevice, pipeline::RenderPipeline, bind_group::BindGroup, bind_group_layout::BindGroupLayout, sampler::{SamplerDesc, EAddressMode, EFilterMode, EAnisotropyClamp}}, renderer::sampler::SamplerRes};
+
+use crate::shaders::SpineShader;
+
+/// 将一张纹理整体绘制到当前视口, 用于多重采样结果写回 Atlas 区域
+pub struct SpineBlit {
+    shader: SpineShader,
+    layout: BindGroupLayout,
+    sampler: SamplerRes,
+    pipelines: XHashMap<wgpu::TextureFormat, Arc<RenderPipeline>>,
+}
+impl SpineBlit {
+    pub const SAMPLER_DESC: SamplerDesc = SamplerDesc {
+        address_mode_u: EAddressMode::ClampToEdge,
+        address_mode_v: EAddressMode::ClampToEdge,
+        address_mode_w: EAddressMode::ClampToEdge,
+        mag_filter: EFilterMode::Nearest,
+        min_filter: EFilterMode::Nearest,
+        mipmap_filter: EFilterMode::Nearest,
+        compare: None,
+        anisotropy_clamp: EAnisotropyClamp::One,
+        border_color: None,
+    };
+    pub fn new(device: &RenderDevice) -> Self {
+        let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blit-VS"),
+            source: wgpu::ShaderSource::Glsl {
+                shader: std::borrow::Cow::Borrowed(include_str!("./shaders/blit.vert")),
+                stage: naga::ShaderStage::Vertex,
+                defines: naga::FastHashMap::default(),
+            },
+        });
+        let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blit-FS"),
+            source: wgpu::ShaderSource::Glsl {
+                shader: std::borrow::Cow::Borrowed(include_str!("./shaders/blit.frag")),
+                stage: naga::ShaderStage::Fragment,
+                defines: naga::FastHashMap::default(),
+            },
+        });
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some("Blit"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            }
+        );
+        Self {
+            shader: SpineShader { vs, vs_point: "main", fs, fs_point: "main" },
+            layout,
+            sampler: SamplerRes::new(device, &Self::SAMPLER_DESC),
+            pipelines: XHashMap::default(),
+        }
+    }
+    pub fn pipeline(&mut self, device: &RenderDevice, format: wgpu::TextureFormat) -> Arc<RenderPipeline> {
+        if let Some(pipeline) = self.pipelines.get(&format) {
+            return pipeline.clone();
+        }
+
+        let bind_group_layouts: [&wgpu::BindGroupLayout; 1] = [&self.layout];
+        let pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &bind_group_layouts,
+                push_constant_ranges: &[],
+            }
+        );
+        let pipeline = device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: Some("SpineBlit"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader.vs,
+                    entry_point: self.shader.vs_point,
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader.fs,
+                    entry_point: self.shader.fs_point,
+                    targets: &[Some(wgpu::ColorTargetState { format, blend: None, write_mask: wgpu::ColorWrites::ALL })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            }
+        );
+        let pipeline = Arc::new(pipeline);
+        self.pipelines.insert(format, pipeline.clone());
+        pipeline
+    }
+    pub fn bind_group(&self, device: &RenderDevice, view: &wgpu::TextureView) -> BindGroup {
+        device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: Some("Blit"),
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler.0),
+                    },
+                ],
+            }
+        )
+    }
+}
+
+/// Atlas 目标的多重采样中间纹理, 解析后经 SpineBlit 写入目标区域.
+/// 解析目标须与多重采样纹理尺寸相同且为整张纹理, 不能直接解析到 Atlas 中的子区域, 因此需要中间纹理与一次绘制
+pub struct SpineMsaaTarget {
+    pub width: u32,
+    pub height: u32,
+    pub sample_count: u32,
+    pub format: wgpu::TextureFormat,
+    _texture: wgpu::Texture,
+    pub(crate) view: wgpu::TextureView,
+    _resolve_texture: wgpu::Texture,
+    pub(crate) resolve: wgpu::TextureView,
+    bindgroup: BindGroup,
+    pipeline: Arc<RenderPipeline>,
+}
+impl SpineMsaaTarget {
+    pub fn new(device: &RenderDevice, blit: &mut SpineBlit, width: u32, height: u32, sample_count: u32, format: wgpu::TextureFormat) -> Self {
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+        let texture = (**device).create_texture(&wgpu::TextureDescriptor {
+            label: Some("SpineMsaa"),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let resolve_texture = (**device).create_texture(&wgpu::TextureDescriptor {
+            label: Some("SpineMsaaResolve"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let resolve = resolve_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bindgroup = blit.bind_group(device, &resolve);
+        let pipeline = blit.pipeline(device, format);
+
+        Self { width, height, sample_count, format, _texture: texture, view, _resolve_texture: resolve_texture, resolve, bindgroup, pipeline }
+    }
+    pub fn is_match(&self, width: u32, height: u32, sample_count: u32, format: wgpu::TextureFormat) -> bool {
+        self.width == width && self.height == height && self.sample_count == sample_count && self.format == format
+    }
+    /// 在当前视口内绘制解析后的结果
+    pub fn blit<'a>(&'a self, renderpass: &mut wgpu::RenderPass<'a>) {
+        renderpass.set_pipeline(&self.pipeline);
+        renderpass.set_bind_group(0, &self.bindgroup, &[]);
+        renderpass.draw(0..3, 0..1);
+    }
+}
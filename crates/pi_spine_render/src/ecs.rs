@@ -1 +1 @@
-pub use bevy_ecs::{prelude::{ResMut, Res, Commands, Changed, With, Or}, system::{Command, EntityCommands}, query::WorldQuery};
+pub use bevy_ecs::{prelude::{ResMut, Res, Commands, Changed, With, Or}, system::{Command, EntityCommands}, query::WorldQuery};
@@ -0,0 +1,86 @@
+use std::fmt::Display;
+
+use bevy_ecs::prelude::Event;
+
+use pi_atom::Atom;
+
+use crate::{KeySpineRenderer, shaders::KeySpineShader, texture::ESpineTextureError};
+
+/// 绘制流程中被跳过的原因, draw 为自上次 Reset 起 Draw 命令的序号;
+/// 保留的绘制每帧重建, 其错误每帧都会报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpineRenderError {
+    /// 渲染器不存在
+    MissingRenderer,
+    /// Draw 前未设置 Shader
+    MissingShader { draw: usize },
+    /// Draw 前未设置 Uniform
+    MissingUniform { draw: usize },
+    MissingTexture { draw: usize, shader: KeySpineShader },
+    MissingSampler { draw: usize, shader: KeySpineShader },
+    /// 顶点 Buffer 分配失败
+    VertexAllocation { draw: usize, bytes: usize },
+    /// 索引 Buffer 分配失败
+    IndexAllocation { draw: usize, bytes: usize },
+    /// Uniform Buffer 未创建或 BindGroup 缓存已满
+    BindGroup { draw: usize, shader: KeySpineShader },
+    /// 管线创建或缓存失败
+    Pipeline { draw: usize, shader: KeySpineShader },
+    /// Skinned 绘制的网格不存在
+    MissingMesh { draw: usize },
+    /// Skinned 绘制前未设置骨骼矩阵
+    MissingBones { draw: usize },
+    /// 清屏矩形未能创建, 本帧未清屏
+    Clear,
+    /// 目标格式不保证支持该多重采样数, 设置被忽略
+    SampleCount { count: u32, format: wgpu::TextureFormat },
+    /// 目标格式不可渲染、不可混合或不可过滤采样, 设置被忽略
+    TargetFormat(wgpu::TextureFormat),
+    /// 渲染图节点创建或依赖设置失败
+    Graph { message: String },
+    /// 渲染器使用的纹理局部更新失败
+    TextureRegion { key: u64, error: ESpineTextureError },
+    /// 渲染器使用的纹理没有保留纹理对象, 无法局部更新
+    MissingTextureSource { key: u64 },
+}
+impl Display for SpineRenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingRenderer => write!(f, "renderer not found"),
+            Self::MissingShader { draw } => write!(f, "draw {}: no shader", draw),
+            Self::MissingUniform { draw } => write!(f, "draw {}: no uniform", draw),
+            Self::MissingTexture { draw, shader } => write!(f, "draw {}: {:?} without texture", draw, shader),
+            Self::MissingSampler { draw, shader } => write!(f, "draw {}: {:?} without sampler", draw, shader),
+            Self::VertexAllocation { draw, bytes } => write!(f, "draw {}: vertex buffer allocation of {} bytes failed", draw, bytes),
+            Self::IndexAllocation { draw, bytes } => write!(f, "draw {}: index buffer allocation of {} bytes failed", draw, bytes),
+            Self::BindGroup { draw, shader } => write!(f, "draw {}: {:?} bind group unavailable", draw, shader),
+            Self::Pipeline { draw, shader } => write!(f, "draw {}: {:?} pipeline unavailable", draw, shader),
+            Self::MissingMesh { draw } => write!(f, "draw {}: no skinned mesh", draw),
+            Self::MissingBones { draw } => write!(f, "draw {}: no bones", draw),
+            Self::Clear => write!(f, "clear draw unavailable"),
+            Self::SampleCount { count, format } => write!(f, "sample count {} unsupported for {:?}", count, format),
+            Self::TargetFormat(format) => write!(f, "target format {:?} not renderable, blendable and filterable", format),
+            Self::Graph { message } => write!(f, "render graph: {}", message),
+            Self::TextureRegion { key, error } => write!(f, "texture {} region update: {}", key, error),
+            Self::MissingTextureSource { key } => write!(f, "texture {} has no retained texture object", key),
+        }
+    }
+}
+impl std::error::Error for SpineRenderError {}
+
+/// 每帧对每个错误发送一次
+#[derive(Debug, Clone, Event)]
+pub struct SpineRenderErrorEvent {
+    /// 渲染器组的错误 (如 CreateGroup 失败) 为组的实体
+    pub renderer: KeySpineRenderer,
+    pub error: SpineRenderError,
+}
+
+/// 纹理加载在重试次数用尽后仍失败时发送
+#[derive(Debug, Clone, Event)]
+pub struct SpineTextureLoadFailEvent {
+    pub key: Atom,
+    pub error: String,
+    /// 已尝试的次数, 含首次加载
+    pub attempts: u32,
+}
@@ -0,0 +1,274 @@
+use bevy_ecs::{prelude::{Entity, World}, system::SystemState};
+use pi_bevy_render_plugin::{node::{Node, ParamUsage}, SimpleInOut, NodeId, RenderContext};
+use pi_null::Null;
+use pi_render::components::view::target_alloc::ShareTargetView;
+use wgpu::StoreOp;
+
+use crate::{KeySpineRenderer, SpineRenderContext, SpineRenderOutput, SpineTargetChangedEvent, allocate_atlas_target};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct KeySpineGroup(pub Entity);
+
+/// 渲染器组: 成员共用一个 Atlas 目标, 在同一 Pass 内绘制到各自的子区域
+pub struct SpineGroupParam {
+    pub(crate) members: Vec<KeySpineRenderer>,
+    pub(crate) node: NodeId,
+    pub(crate) format: wgpu::TextureFormat,
+}
+impl SpineGroupParam {
+    pub fn new(format: wgpu::TextureFormat) -> Self {
+        Self { members: vec![], node: NodeId::null(), format }
+    }
+    pub fn members(&self) -> &[KeySpineRenderer] {
+        &self.members
+    }
+}
+
+/// 成员之间的间隔, 避免线性采样时相互渗色
+pub const GROUP_PADDING: u32 = 1;
+
+/// 按高度降序的货架排布, 返回各尺寸的偏移及总宽高
+pub fn shelf_pack(sizes: &[(u32, u32)]) -> (Vec<(u32, u32)>, u32, u32) {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|a, b| sizes[*b].1.cmp(&sizes[*a].1));
+
+    let area: u64 = sizes.iter().map(|(w, h)| (*w + GROUP_PADDING) as u64 * (*h + GROUP_PADDING) as u64).sum();
+    let max_width = sizes.iter().map(|(w, _)| *w).max().unwrap_or(0);
+    let limit = max_width.max((area as f64).sqrt().ceil() as u32);
+
+    let mut offsets = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut shelf, mut width) = (0, 0, 0, 0);
+    order.iter().for_each(|index| {
+        let (w, h) = sizes[*index];
+        if x > 0 && x + w > limit {
+            y += shelf + GROUP_PADDING;
+            x = 0;
+            shelf = 0;
+        }
+        offsets[*index] = (x, y);
+        width = width.max(x + w);
+        shelf = shelf.max(h);
+        x += w + GROUP_PADDING;
+    });
+
+    (offsets, width, y + shelf)
+}
+
+pub struct SpineGroupRenderNode {
+    pub group: KeySpineGroup,
+    rt: Option<ShareTargetView>,
+    /// 成员及其尺寸与格式; 不变时复用 rt
+    rt_key: Option<(Vec<(KeySpineRenderer, u32, u32)>, wgpu::TextureFormat)>,
+    /// 各成员在目标纹理中的区域
+    rects: Vec<(KeySpineRenderer, (u32, u32, u32, u32))>,
+    generation: u64,
+}
+impl SpineGroupRenderNode {
+    pub fn new(group: KeySpineGroup) -> Self {
+        Self { group, rt: None, rt_key: None, rects: vec![], generation: 0 }
+    }
+    /// 清除仍属于本组的成员已发布的输出, 使其不再持有旧区域
+    fn release_outputs(&self, world: &mut World) {
+        let mut spine_ctx = world.get_resource_mut::<SpineRenderContext>().unwrap();
+        self.rects.iter().for_each(|(key, _)| {
+            if let Some(renderer) = spine_ctx.list.get_mut(key).filter(|renderer| renderer.group == Some(self.group)) {
+                renderer.output = None;
+            }
+        });
+    }
+}
+
+impl Node for SpineGroupRenderNode {
+    type Input = SimpleInOut;
+
+    type Output = SimpleInOut;
+
+    type BuildParam = ();
+    type RunParam = ();
+
+    fn build<'a>(
+        &'a mut self,
+        world: &'a mut World,
+        _param: &'a mut SystemState<Self::BuildParam>,
+        _context: RenderContext,
+        _input: &'a Self::Input,
+        _usage: &'a ParamUsage,
+        _id: NodeId,
+        _from: &'a [NodeId],
+        _to: &'a [NodeId],
+    ) -> Result<Self::Output, String> {
+        let spine_ctx = world.get_resource::<SpineRenderContext>().unwrap();
+        let group = if let Some(group) = spine_ctx.groups.get(&self.group) {
+            group
+        } else {
+            log::warn!("SpineGraph:: None group");
+            return Ok(SimpleInOut { target: None, valid_rect: None });
+        };
+        let format = group.format;
+        let members: Vec<(KeySpineRenderer, u32, u32)> = group.members.iter().filter_map(|key| {
+            spine_ctx.list.get(key).map(|renderer| (*key, renderer.width, renderer.height))
+        }).collect();
+
+        if members.is_empty() {
+            self.release_outputs(world);
+            self.rt = None;
+            self.rt_key = None;
+            self.rects.clear();
+            return Ok(SimpleInOut { target: None, valid_rect: None });
+        }
+
+        let key = (members, format);
+        if self.rt.is_none() || self.rt_key.as_ref() != Some(&key) {
+            let sizes: Vec<(u32, u32)> = key.0.iter().map(|(_, w, h)| (*w, *h)).collect();
+            let (offsets, width, height) = shelf_pack(&sizes);
+
+            // 先释放旧区域再分配, 成员已发布的输出也持有旧区域, 需一并清除
+            self.release_outputs(world);
+            self.rt = None;
+            let target = allocate_atlas_target(world, width, height, format);
+            let rect = target.rect();
+            let (x, y) = (rect.min.x as u32, rect.min.y as u32);
+            self.rects = key.0.iter().zip(offsets.iter()).map(|((renderer, w, h), (ox, oy))| {
+                (*renderer, (x + ox, y + oy, *w, *h))
+            }).collect();
+            self.rt = Some(target);
+            self.rt_key = Some(key);
+            self.generation += 1;
+
+            let generation = self.generation;
+            self.rects.iter().for_each(|(renderer, (_, _, width, height))| {
+                world.send_event(SpineTargetChangedEvent { renderer: *renderer, width: *width, height: *height, format, generation });
+            });
+        }
+
+        let target = self.rt.clone().unwrap();
+        let mut spine_ctx = world.get_resource_mut::<SpineRenderContext>().unwrap();
+        self.rects.iter().for_each(|(key, rect)| {
+            if let Some(renderer) = spine_ctx.list.get_mut(key) {
+                renderer.output = Some(SpineRenderOutput { target: target.clone(), rect: *rect, generation: self.generation });
+            }
+        });
+
+        Ok(SimpleInOut { target: Some(target), valid_rect: None })
+    }
+
+    fn run<'a>(
+        &'a mut self,
+        world: &'a bevy_ecs::prelude::World,
+        _param: &'a mut bevy_ecs::system::SystemState<Self::RunParam>,
+        _context: pi_bevy_render_plugin::RenderContext,
+        commands: pi_share::ShareRefCell<wgpu::CommandEncoder>,
+        _input: &'a Self::Input,
+        _usage: &'a pi_bevy_render_plugin::node::ParamUsage,
+        _id: NodeId,
+        _from: &[NodeId],
+        _to: &[NodeId],
+    ) -> pi_futures::BoxFuture<'a, Result<(), String>> {
+        let spine_ctx = world.get_resource::<SpineRenderContext>().unwrap();
+
+        Box::pin(
+            async move {
+                let target = match &self.rt {
+                    Some(r) => r,
+                    None => return Ok(()),
+                };
+                // 未变化的成员保留上次的内容
+                let active = self.rects.iter().any(|(key, _)| {
+                    spine_ctx.list.get(key).map(|renderer| renderer.render.is_active()).unwrap_or(false)
+                });
+                if !active {
+                    return Ok(());
+                }
+
+                let mut encoder = commands.0.as_ref().borrow_mut();
+                let mut renderpass = encoder.begin_render_pass(
+                    &wgpu::RenderPassDescriptor {
+                        label: Some("SpineGroup"),
+                        color_attachments: &[
+                            Some(
+                                wgpu::RenderPassColorAttachment {
+                                    view: &target.target().colors[0].0,
+                                    resolve_target: None,
+                                    ops: wgpu::Operations {
+                                        load: wgpu::LoadOp::Load,
+                                        store: StoreOp::Store,
+                                    }
+                                }
+                            )
+                        ],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    }
+                );
+                let bounds = (target.target().width, target.target().height);
+                self.rects.iter().for_each(|(key, rect)| {
+                    if let Some(renderer) = spine_ctx.list.get(key).filter(|renderer| renderer.render.is_active()) {
+                        renderer.render.render_rect(&mut renderpass, *rect, bounds);
+                    }
+                });
+
+                Ok(())
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shelf_pack, GROUP_PADDING};
+
+    /// 各区域在总宽高内, 且相互之间至少间隔 GROUP_PADDING
+    fn check(sizes: &[(u32, u32)]) {
+        let (offsets, width, height) = shelf_pack(sizes);
+        assert_eq!(offsets.len(), sizes.len());
+        let rects: Vec<_> = offsets.iter().zip(sizes).map(|((x, y), (w, h))| (*x, *y, *x + *w, *y + *h)).collect();
+        rects.iter().for_each(|(_, _, right, bottom)| {
+            assert!(*right <= width && *bottom <= height);
+        });
+        for (i, a) in rects.iter().enumerate() {
+            for b in rects.iter().skip(i + 1) {
+                let apart = a.2 + GROUP_PADDING <= b.0 || b.2 + GROUP_PADDING <= a.0
+                    || a.3 + GROUP_PADDING <= b.1 || b.3 + GROUP_PADDING <= a.1;
+                assert!(apart, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(shelf_pack(&[]), (vec![], 0, 0));
+    }
+
+    #[test]
+    fn single() {
+        assert_eq!(shelf_pack(&[(30, 40)]), (vec![(0, 0)], 30, 40));
+    }
+
+    #[test]
+    fn shelves_by_height() {
+        let (offsets, width, height) = shelf_pack(&[(10, 10), (10, 20), (10, 5)]);
+        assert_eq!(offsets, vec![(11, 0), (0, 0), (0, 21)]);
+        assert_eq!((width, height), (21, 26));
+    }
+
+    #[test]
+    fn wide_member_fits() {
+        check(&[(200, 4), (8, 8), (8, 8), (8, 8)]);
+        let (_, width, _) = shelf_pack(&[(200, 4), (8, 8)]);
+        assert_eq!(width, 200);
+    }
+
+    #[test]
+    fn no_overlap() {
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 16) % 128 + 1
+        };
+        for count in 1..24 {
+            let sizes: Vec<_> = (0..count).map(|_| (next(), next())).collect();
+            check(&sizes);
+        }
+    }
+}
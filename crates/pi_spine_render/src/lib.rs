@@ -1,785 +1,1757 @@
-
-use std::mem::replace;
-
-use bevy_ecs::{prelude::{Query, ResMut, Resource, Res, IntoSystemConfigs, Entity, Commands, SystemSet, apply_deferred}, world::World, system::SystemState};
-use bevy_app::prelude::{Update, App, Plugin};
-use crossbeam::queue::SegQueue;
-use futures::FutureExt;
-use pi_assets::{mgr::{AssetMgr, LoadResult}, asset::{Handle, GarbageEmpty}};
-use pi_async_rt::prelude::AsyncRuntime;
-use pi_atom::Atom;
-use pi_bevy_asset::ShareAssetMgr;
-use pi_bevy_render_plugin::{
-    PiRenderDevice, PiRenderQueue, node::{Node, ParamUsage}, PiSafeAtlasAllocator, SimpleInOut, PiClearOptions, PiRenderGraph, NodeId, GraphError, PiRenderSystemSet, render_cross::GraphId, PiRenderOptions,
-    constant::texture_sampler::*, RenderContext
-};
-use pi_null::Null;
-// use pi_window_renderer::WindowRenderer;
-use pi_hal::{runtime::RENDER_RUNTIME, loader::AsyncLoader};
-use pi_hash::XHashMap;
-use pi_render::{rhi::{sampler::{SamplerDesc, EAddressMode, EFilterMode, EAnisotropyClamp}, asset::{TextureRes, ImageTextureDesc}}, asset::TAssetKeyU64, renderer::{sampler::SamplerRes, draw_obj_list::DrawList}, components::view::target_alloc::{ShareTargetView, TargetDescriptor, TextureDescriptor}};
-use pi_share::Share;
-use renderer::{RendererAsync, SpineResource};
-use shaders::KeySpineShader;
-use smallvec::SmallVec;
-use wgpu::StoreOp;
-
-
-pub mod binds;
-pub mod bind_groups;
-pub mod shaders;
-pub mod vertex_buffer;
-pub mod renderer;
-pub mod ecs;
-
-pub const FORMAT: ColorFormat = ColorFormat::Rgba8Unorm;
-pub const SAMPLER_DESC: SamplerDesc = SamplerDesc {
-    address_mode_u: EAddressMode::Repeat,
-    address_mode_v: EAddressMode::Repeat,
-    address_mode_w: EAddressMode::Repeat,
-    mag_filter: EFilterMode::Linear,
-    min_filter: EFilterMode::Linear,
-    mipmap_filter: EFilterMode::Nearest,
-    compare: None,
-    anisotropy_clamp: EAnisotropyClamp::One,
-    border_color: None,
-};
-
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-pub struct KeySpineRenderer(pub Entity);
-impl KeySpineRenderer {
-    pub fn from_f64(val: f64) -> Self {
-        Self(Entity::from_bits(val.to_bits()))
-    }
-}
-
-pub struct SpineRenderNodeParam {
-    render: RendererAsync,
-    width: u32,
-    height: u32,
-    to_screen: bool,
-}
-impl SpineRenderNodeParam {
-    pub fn render_mut(&mut self) -> &mut RendererAsync {
-        &mut self.render
-    }
-}
-
-pub struct SpineRenderNode{
-	pub renderer: KeySpineRenderer,
-	rt: Option<ShareTargetView>,
-}
-
-impl SpineRenderNode {
-	pub fn new(renderer: KeySpineRenderer) -> Self {
-		Self { renderer, rt: None }
-	}
-}
-
-impl Node for SpineRenderNode {
-    type Input = ();
-
-    type Output = SimpleInOut;
-
-	type BuildParam = ();
-    type RunParam = ();
-
-	fn build<'a>(
-        &'a mut self,
-        world: &'a mut World,
-        param: &'a mut SystemState<Self::BuildParam>,
-        context: RenderContext,
-		input: &'a Self::Input,
-        usage: &'a ParamUsage,
-		id: NodeId,
-		from: &'a [NodeId],
-		to: &'a [NodeId],
-    ) -> Result<Self::Output, String> {
-		let spine_ctx = world.get_resource::<SpineRenderContext>().unwrap();
-		let renderer = if let Some(renderer) = spine_ctx.list.get(&self.renderer) {
-			renderer
-		} else {
-			log::warn!("SpineGraph:: None renderer");
-			return Ok(SimpleInOut { target: None, valid_rect: None })
-		};
-
-		if renderer.to_screen == false {
-			let temp: Vec<ShareTargetView> = vec![];
-			let atlas_allocator = world.get_resource::<PiSafeAtlasAllocator>().unwrap();
-			let target_type = atlas_allocator.get_or_create_type(
-				TargetDescriptor {
-					colors_descriptor: SmallVec::from_slice(
-						&[
-							TextureDescriptor {
-								mip_level_count: 1,
-								sample_count: 1,
-								dimension: wgpu::TextureDimension::D2,
-								format: FORMAT.val(),
-								usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::RENDER_ATTACHMENT,
-								base_mip_level: 0,
-								base_array_layer: 0,
-								array_layer_count: None,
-								view_dimension: None,
-							}
-						]
-					),
-					need_depth: false,
-					default_width: 2048,
-					default_height: 2048,
-					depth_descriptor: None,
-				}
-			);
-
-			let target = atlas_allocator.allocate(renderer.width, renderer.height, target_type, temp.iter());
-			self.rt = Some(target.clone());
-			Ok(SimpleInOut { target: Some(target), valid_rect: None })
-		} else {
-			Ok(SimpleInOut { target: None, valid_rect: None })
-		}
-	}
-
-    fn run<'a>(
-        &'a mut self,
-        world: &'a bevy_ecs::prelude::World,
-        param: &'a mut bevy_ecs::system::SystemState<Self::RunParam>,
-        _context: pi_bevy_render_plugin::RenderContext,
-        commands: pi_share::ShareRefCell<wgpu::CommandEncoder>,
-        _input: &'a Self::Input,
-        _usage: &'a pi_bevy_render_plugin::node::ParamUsage,
-		_id: NodeId,
-		_from: &[NodeId],
-		_to: &[NodeId],
-    ) -> pi_futures::BoxFuture<'a, Result<(), String>> {
-        let atlas_allocator = world.get_resource::<PiSafeAtlasAllocator>().unwrap();
-        let temp: Vec<ShareTargetView> = vec![];
-        
-        let spine_ctx = world.get_resource::<SpineRenderContext>().unwrap();
-
-        let renderer = if let Some(renderer) = spine_ctx.list.get(&self.renderer) {
-            renderer
-        } else {
-            return async move {
-                log::warn!("SpineGraph:: None renderer");
-                Ok(())
-            }.boxed();
-        };
-
-		return Box::pin(
-            async move {
-                
-                let mut encoder = commands.0.as_ref().borrow_mut();
-
-
-                let target = match &self.rt {
-                    Some(r) => r,
-                    None => return  Ok(()),
-                };
-                
-                {
-                    let _renderpass = encoder.begin_render_pass(
-                        &wgpu::RenderPassDescriptor {
-                            label: Some("RenderNode"),
-                            color_attachments: &[
-                                Some(
-                                    wgpu::RenderPassColorAttachment {
-                                        view: &target.target().colors[0].0,
-                                        resolve_target: None,
-                                        ops: wgpu::Operations {
-                                            load: wgpu::LoadOp::Clear(wgpu::Color { r: 0., g: 0., b: 0., a: 0. }),
-                                            store: StoreOp::Store,
-                                        }
-                                    }
-                                )
-                            ],
-                            depth_stencil_attachment: None,
-							timestamp_writes: None,
-                            occlusion_query_set: None,
-                        }
-                    );
-                }
-                {
-                    let mut renderpass = encoder.begin_render_pass(
-                        &wgpu::RenderPassDescriptor {
-                            label: Some("RenderNode"),
-                            color_attachments: &[
-                                Some(
-                                    wgpu::RenderPassColorAttachment {
-                                        view: &target.target().colors[0].0,
-                                        resolve_target: None,
-                                        ops: wgpu::Operations {
-                                            load: wgpu::LoadOp::Load,
-                                            store: StoreOp::Store,
-                                        }
-                                    }
-                                )
-                            ],
-                            depth_stencil_attachment: None,
-                            timestamp_writes: None,
-                            occlusion_query_set: None,
-                        }
-                    );
-                    let rect = target.rect();
-                    let (x, y, w, h) = (
-                        rect.min.x as u32,
-                        rect.min.y as u32,
-                        (rect.max.x - rect.min.x) as u32,
-                        (rect.max.y - rect.min.y) as u32,
-                    );
-            
-                    let min_depth = 0.;
-                    let max_depth = 1.;
-            
-                    renderpass.set_viewport(x as f32, y as f32, w as f32, h as f32, min_depth, max_depth);
-                    renderpass.set_scissor_rect(x as u32, y as u32, w as u32, h as u32);
-                    // log::warn!("SpineGraph DrawList::render: {:?}", renderer.render.drawobjs.list.len());
-                    DrawList::render(renderer.render.drawobjs.list.as_slice(), &mut renderpass);
-                }
-
-                Ok(())
-        	});
-        
-		// let screen = param.get(world);
-
-		// Box::pin(async move {
-		//     if let Some(view) = screen.view() {
-		//         let mut encoder = commands.0.as_ref().borrow_mut();
-		//         let mut renderpass = encoder.begin_render_pass(
-		//             &wgpu::RenderPassDescriptor {
-		//                 label: None,
-		//                 color_attachments: &[
-		//                     Some(
-		//                         wgpu::RenderPassColorAttachment {
-		//                             view: &view,
-		//                             resolve_target: None,
-		//                             ops: wgpu::Operations {
-		//                                 load: wgpu::LoadOp::Load,
-		//                                 store: true,
-		//                             }
-		//                         }
-		//                     )
-		//                 ],
-		//                 depth_stencil_attachment: None,
-		//             }
-		//         );
-				
-		//         // renderpass.set_viewport(x, y, w, h, min_depth, max_depth);
-		//         // renderpass.set_scissor_rect(x as u32, y as u32, w as u32, h as u32);
-		//         // log::warn!("SpineGraph Draws: {:?}", renderer.render.drawobjs.list.len());
-		//         DrawList::render(renderer.render.drawobjs.list.as_slice(), &mut renderpass);
-		//     }
-
-		//     Ok(SimpleInOut { target: None, valid_rect: None })
-		// })
-    }
-}
-
-
-#[derive(Resource)]
-pub struct SpineRenderContext {
-    list: XHashMap<KeySpineRenderer, SpineRenderNodeParam>,
-}
-impl SpineRenderContext {
-    pub fn new() -> Self {
-        Self { list: XHashMap::default() }
-    }
-    pub fn get_mut(&mut self, key: KeySpineRenderer) -> Option<&mut SpineRenderNodeParam> {
-        self.list.get_mut(&key)
-    }
-    pub fn create_renderer(&mut self, key: KeySpineRenderer, to_screen: bool) {
-        // self.counter += 1;
-        // let id = self.counter;
-
-        let render = SpineRenderNodeParam { render: RendererAsync::new(), width: 128, height: 128, to_screen };
-        self.list.insert(key, render);
-    }
-}
-
-#[derive(Clone)]
-pub enum ESpineCommand {
-    Create(KeySpineRenderer, String, Option<(u32, u32)>, wgpu::TextureFormat),
-    Dispose(KeySpineRenderer),
-    TextureLoad(Atom),
-    TextureRecord(KeySpineRenderer, Handle<TextureRes>),
-    SamplerRecord(KeySpineRenderer, SamplerDesc, Handle<SamplerRes>),
-    RemoveTextureRecord(KeySpineRenderer, u64),
-    Reset(KeySpineRenderer),
-    RenderSize(KeySpineRenderer, u32, u32),
-    Shader(KeySpineRenderer, Option<KeySpineShader>),
-    UseTexture(KeySpineRenderer, Option<Handle<TextureRes>>, Option<Handle<SamplerRes>>),
-    Texture(KeySpineRenderer, u64, Handle<TextureRes>, SamplerDesc, Handle<SamplerRes>),
-    Blend(KeySpineRenderer, bool),
-    BlendMode(KeySpineRenderer, wgpu::BlendFactor, wgpu::BlendFactor),
-    Uniform(KeySpineRenderer, Vec<f32>),
-    Draw(KeySpineRenderer, Vec<f32>, Vec<u16>, u32, u32),
-    Graph(KeySpineRenderer, NodeId),
-}
-
-
-pub type ActionListSpine = pi_bevy_ecs_extend::action::ActionList<ESpineCommand>;
-
-pub fn sys_spine_cmds(
-    mut cmds: ResMut<ActionListSpine>,
-    mut clearopt: ResMut<PiClearOptions>,
-    mut renderers: ResMut<SpineRenderContext>,
-    renderopt: Res<PiRenderOptions>,
-    mut graphic: ResMut<PiRenderGraph>,
-    mut texloader: ResMut<SpineTextureLoad>,
-    nodes: Query<&GraphId>,
-    mut commands: Commands,
-) {
-    clearopt.color.g = 0.;
-    let mut list = cmds.drain();
-    // let len = list.len();
-    let mut index = 0;
-    list.drain(..).for_each(|cmd| {
-        index += 1;
-        match cmd {
-            ESpineCommand::Create(id, name, rendersize, format) => {
-                ActionSpine::create_spine_renderer(id, rendersize, &mut renderers, format);
-                match ActionSpine::spine_renderer_apply(id, pi_atom::Atom::from(name), rendersize.is_none(), &mut graphic) {
-                    Ok(nodeid) => {
-                        if let Some(mut cmd) = commands.get_entity(id.0) {
-                            cmd.insert(GraphId(nodeid));
-                        }
-                    },
-                    Err(e) => {
-                        log::warn!("Spine render_graph Err {:?}", e);
-                    },
-                }
-            },
-            ESpineCommand::Dispose(id_renderer) => {
-                if let Ok(nodeid) = nodes.get(id_renderer.0) {
-                    graphic.remove_node(nodeid.0);
-                }
-                ActionSpine::dispose_spine_renderer(id_renderer, &mut renderers);
-                if let Some(mut cmds) = commands.get_entity(id_renderer.0) {
-                    cmds.despawn();
-                }
-            },
-            ESpineCommand::TextureLoad(key) => {
-                texloader.load(key);
-            },
-            ESpineCommand::TextureRecord(id_renderer, val) => {
-                if let Some(renderer) = renderers.get_mut(id_renderer) {
-                    // log::warn!("Cmd: Texture");
-                    let key_u64 = val.key();
-                    renderer.render_mut().record_texture(*key_u64, val);
-                }
-            },
-            ESpineCommand::SamplerRecord(id_renderer, samplerdesc, val) => {
-                if let Some(renderer) = renderers.get_mut(id_renderer) {
-                    // log::warn!("Cmd: Texture");
-                    renderer.render_mut().record_sampler(samplerdesc, val);
-                }
-            },
-            ESpineCommand::RemoveTextureRecord(id_renderer, key) => {
-                if let Some(renderer) = renderers.get_mut(id_renderer) {
-                    // log::warn!("Cmd: Texture");
-                    renderer.render_mut().remove_texture(key);
-                }
-            },
-            ESpineCommand::Uniform(id, val) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    // log::warn!("Cmd: Uniform");
-                    renderer.render.uniform(val);
-                }
-            },
-            ESpineCommand::Shader(id, val) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    // log::warn!("Cmd: Shader");
-                    renderer.render.shader(val);
-                }
-            },
-            ESpineCommand::UseTexture(id, val, sampler) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    // log::warn!("Cmd: UseTexture");
-                    renderer.render.texture(val, sampler);
-                }
-            },
-            ESpineCommand::Draw(id, vertices, indices, vlen, ilen) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    // log::warn!("Cmd: Draw");
-                    // log::warn!("Cmd Draw: {:?} in {:?}", index, len);
-                    renderer.render.draw(vertices, Some(indices), vlen, ilen, &renderopt);
-                }
-            },
-            ESpineCommand::Texture(id, key, value, key2, value2) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    // log::warn!("Cmd: Texture");
-                    renderer.render.textures.insert(key, value);
-                    renderer.render.samplers.insert(key2, value2);
-                }
-            },
-            ESpineCommand::RenderSize(id, width, height) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    renderer.width = width;
-                    renderer.height = height;
-                }
-            },
-            ESpineCommand::Reset(id) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    // log::warn!("Cmd Reset: {:?} in {:?}", index, len);
-                    renderer.render.reset();
-                }
-            },
-            ESpineCommand::Blend(id, val) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    renderer.render.blend(val);
-                }
-            }
-            ,
-            ESpineCommand::BlendMode(id, val0, val1) => {
-                if let Some(renderer) = renderers.list.get_mut(&id) {
-                    renderer.render.blend_mode(val0, val1);
-                }
-            },
-            ESpineCommand::Graph(id, node) => {
-                commands.entity(id.0).insert(GraphId(node));
-            },
-        }
-    })
-}
-
-pub fn sys_spine_render_apply(
-    mut renderers: ResMut<SpineRenderContext>,
-    mut resource: ResMut<SpineResource>,
-    device: Res<PiRenderDevice>,
-    queue: Res<PiRenderQueue>,
-    asset_samplers: Res<ShareAssetMgr<SamplerRes>>,
-    asset_textures: Res<ShareAssetMgr<TextureRes>>,
-) {
-    // log::warn!("Apply: {:?}", renderers.list.len());
-    renderers.list.iter_mut().for_each(|(_, v)| {
-        v.render.drawlist(&device, &queue, &mut resource, &asset_samplers, &asset_textures);
-    });
-    resource.verticeallocator.upload(&queue);
-    resource.indicesallocator.upload(&queue);
-}
-
-// pub trait TInterfaceSpine: TShell {
-//     fn create_spine_renderer(&mut self, name: Atom, next_node: Option<Atom>) -> KeySpineRenderer;
-//     fn dispose_spine_renderer(&mut self, id_renderer: KeySpineRenderer) -> &mut Self;
-//     fn spine_reset(&mut self, id_renderer: KeySpineRenderer) -> &mut Self;
-//     fn spine_uniform(&mut self, id_renderer: KeySpineRenderer, value: &[f32]) -> &mut Self;
-//     fn spine_shader(&mut self, id_renderer: KeySpineRenderer, value: KeySpineShader) -> &mut Self;
-//     fn spine_use_texture(&mut self, id_renderer: KeySpineRenderer, value: u64) -> &mut Self;
-//     fn spine_draw(&mut self, id_renderer: KeySpineRenderer, vertices: &[f32], indices: &[u16], vlen: u32, ilen: u32) -> &mut Self;
-//     fn spine_texture(&mut self, id_renderer: KeySpineRenderer, key: Atom, data: &[u8], width: u32, height: u32) -> &mut Self;
-// }
-
-pub struct ActionSpine;
-impl ActionSpine {
-    pub fn create_spine_renderer(
-        id: KeySpineRenderer,
-        rendersize: Option<(u32, u32)>,
-        ctx: &mut SpineRenderContext,
-        final_render_format: wgpu::TextureFormat,
-    ) {
-        ctx.create_renderer(id, rendersize.is_none());
-        match rendersize {
-            Some(rendersize) => {
-                ctx.list.get_mut(&id).unwrap().render.target_format = wgpu::TextureFormat::Rgba8Unorm;
-                ctx.list.get_mut(&id).unwrap().width = rendersize.0;
-                ctx.list.get_mut(&id).unwrap().height = rendersize.1;
-            },
-            None => {
-                ctx.list.get_mut(&id).unwrap().render.target_format = final_render_format;
-            },
-        }
-    }
-
-    pub fn spine_renderer_apply(
-        id: KeySpineRenderer,
-        name: Atom,
-        to_screen: bool,
-        render_graph: &mut PiRenderGraph,
-    ) -> Result<NodeId, GraphError> {
-        let key = String::from(name.as_str());
-        match render_graph.add_node(key.clone(), SpineRenderNode::new(id), NodeId::null()) {
-            Ok(v) => {
-                // if to_screen {
-                //     render_graph.add_depend(WindowRenderer::CLEAR_KEY, key.clone());
-                //     render_graph.add_depend(key, WindowRenderer::KEY);
-                // }
-        
-                // render_graph.dump_graphviz();
-                Ok(v)
-            },
-            Err(e) => {
-                Err(e)
-            },
-        }
-    }
-
-    pub fn dispose_spine_renderer(
-        id_renderer: KeySpineRenderer,
-        ctx: &mut SpineRenderContext,
-    ) {
-        ctx.list.remove(&id_renderer);
-    }
-
-    pub fn spine_uniform(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-        value: &[f32],
-    ) {
-        cmds.push(ESpineCommand::Uniform(id_renderer, value.to_vec()));
-    }
-
-    pub fn spine_shader(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-        value: KeySpineShader,
-    ) {
-        cmds.push(ESpineCommand::Shader(id_renderer, Some(value)));
-    }
-
-    pub fn spine_use_texture(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-        value: Handle<TextureRes>,
-        sampler: Handle<SamplerRes>,
-    ) {
-        cmds.push(ESpineCommand::UseTexture(id_renderer, Some(value), Some(sampler)));
-    }
-
-    pub fn spine_draw(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-        vertices: &[f32],
-        indices: &[u16],
-        vlen: u32,
-        ilen: u32,
-    ) {
-        cmds.push(ESpineCommand::Draw(id_renderer, vertices.to_vec(), indices.to_vec(), vlen, ilen));
-    }
-
-    pub fn spine_texture(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-        key: &str,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        device: & PiRenderDevice,
-        queue: & PiRenderQueue,
-        asset_textures: & ShareAssetMgr<TextureRes>,
-        asset_samplers: & ShareAssetMgr<SamplerRes>,
-    ) {
-
-        let key_u64 = key.asset_u64();
-        let texture = if let Some(textureres) = asset_textures.get(&key_u64) {
-            textureres
-        } else {
-            let texture = (***device).create_texture(&wgpu::TextureDescriptor {
-                label: None,
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb]
-            });
-            queue.write_texture(
-                // Tells wgpu where to copy the pixel data
-                wgpu::ImageCopyTexture {
-                    texture: &texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                // The actual pixel data
-                data,
-                // The layout of the texture
-                wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: std::num::NonZeroU32::new(4 * width).map(|r| {r.get()}),
-                    rows_per_image: std::num::NonZeroU32::new(height).map(|r| {r.get()}),
-                },
-                wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-            );
-            let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
-                label: None,
-                format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
-                dimension: Some(wgpu::TextureViewDimension::D2),
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: 0,
-                mip_level_count: None,
-                base_array_layer: 0,
-                array_layer_count: None,
-            });
-
-            let textureres = TextureRes::new(width, height, (width * height * 4) as usize, texture_view, true, wgpu::TextureFormat::Rgba8UnormSrgb);
-            
-            if let Ok(texture) = asset_textures.insert(key_u64, textureres) {
-                texture
-            } else {
-                return;
-            }
-        };
-
-        let samplerdesc = SAMPLER_DESC.clone();
-
-        let sampler = if let Some(sampler) = asset_samplers.get(&samplerdesc) {
-            sampler
-        } else {
-            if let Ok(sampler) = asset_samplers.insert(samplerdesc.clone(), SamplerRes::new(&device, &samplerdesc)) {
-                sampler
-            } else {
-                return;
-            }
-        };
-
-        cmds.push(ESpineCommand::Texture(id_renderer, key_u64, texture, samplerdesc, sampler));
-    }
-
-    pub fn spine_reset(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-    ) {
-        cmds.push(ESpineCommand::Reset(id_renderer));
-    }
-
-    pub fn spine_blend(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-        value: bool,
-    ) {
-        cmds.push(ESpineCommand::Blend(id_renderer, value));
-    }
-
-    pub fn spine_blend_mode(
-        cmds: &mut ActionListSpine,
-        id_renderer: KeySpineRenderer,
-        src: wgpu::BlendFactor,
-        dst: wgpu::BlendFactor,
-    ) {
-        cmds.push(ESpineCommand::BlendMode(id_renderer, src, dst));
-    }
-}
-
-
-#[derive(Resource, Default)]
-pub struct SpineTextureLoad {
-    pub success: Share<SegQueue<(Atom, Handle<TextureRes>)>>,
-    pub fail: Share<SegQueue<(Atom, String)>>,
-    pub list: Vec<Atom>,
-}
-impl SpineTextureLoad {
-    pub fn load(&mut self, key: Atom) {
-        self.list.push(key)
-    }
-}
-
-
-fn sys_spine_texture_load(
-    mut loader: ResMut<SpineTextureLoad>,
-    device: Res<PiRenderDevice>,
-    queue: Res<PiRenderQueue>,
-    texture_assets_mgr: Res<ShareAssetMgr<TextureRes>>,
-) {
-    let mut list = replace(&mut loader.list, vec![]);
-    list.drain(..).for_each(|k| {
-        let result = AssetMgr::load(&texture_assets_mgr, &(k.asset_u64()));
-        match result {
-            LoadResult::Ok(r) => {
-                loader.success.push((k, r));
-            }
-            ,
-            _ => {
-                let success = loader.success.clone();
-                let fail = loader.fail.clone();
-                let device = device.0.clone();
-                let queue = queue.0.clone();
-    
-                RENDER_RUNTIME
-                    .spawn(async move {
-                        let desc = ImageTextureDesc {
-                            url: &k,
-                            device: &device,
-                            queue: &queue,
-                        };
-        
-                        let r = TextureRes::async_load(desc, result).await;
-                        match r {
-                            Ok(r) => {
-                                success.push((k, r));
-                            }
-                            Err(e) => {
-                                fail.push((k, format!("load image fail, {:?}", e)));
-                            }
-                        };
-                    })
-                    .unwrap();
-            }
-        }
-    });
-}
-
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
-pub struct SpineSystemSet;
-
-#[derive(Clone, Resource)]
-pub struct SpineAssetConfig {
-    pub vertex_buffer: (usize, usize),
-    pub bind_buffer: (usize, usize),
-    pub bind_group: (usize, usize),
-}
-impl Default for SpineAssetConfig {
-    fn default() -> Self {
-        Self {
-            vertex_buffer: (10 * 1024 * 1024, 60 * 1024),
-            bind_buffer: (1 * 1024 * 1024, 60 * 1024),
-            bind_group: (100 * 1024, 60 * 1024),
-        }
-    }
-}
-
-#[derive(Default)]
-pub struct PluginSpineRenderer;
-impl Plugin for PluginSpineRenderer {
-    fn build(&self, app: &mut App) {
-        if app.world.get_resource::<ShareAssetMgr<SamplerRes>>().is_none() {
-            app.insert_resource(ShareAssetMgr::<SamplerRes>::new(GarbageEmpty(), false, 32 * 1024, 30 * 1000));
-        }
-        if app.world.get_resource::<ShareAssetMgr<TextureRes>>().is_none() {
-            app.insert_resource(ShareAssetMgr::<TextureRes>::new(GarbageEmpty(), false, 32 * 1024 * 1024, 30 * 1000));
-        }
-
-        let cfg = if let Some(cfg) = app.world.get_resource::<SpineAssetConfig>() {
-            cfg.clone()
-        } else {
-            app.world.insert_resource(SpineAssetConfig::default());
-            app.world.get_resource::<SpineAssetConfig>().unwrap().clone()
-        };
-        
-        let device = app.world.get_resource::<PiRenderDevice>().unwrap().0.clone();
-        app.insert_resource(ActionListSpine::default())
-            .insert_resource(SpineResource::new(&device, cfg.vertex_buffer.clone(), cfg.bind_buffer.clone(), cfg.bind_group.clone()))
-            .insert_resource(SpineRenderContext::new())
-            .insert_resource(SpineTextureLoad::default());
-
-        app.add_systems(
-			Update,
-            (
-                sys_spine_cmds,
-                sys_spine_render_apply,
-                sys_spine_texture_load
-            ).chain().in_set(SpineSystemSet).before(PiRenderSystemSet)
-        );
-
-        app.add_system(apply_deferred.in_set(SpineSystemSet));
-
-        // log::warn!("PluginSpineRenderer");
-    }
+
+use std::mem::replace;
+
+use bevy_ecs::{prelude::{Query, ResMut, Resource, Res, IntoSystemConfigs, Entity, Commands, SystemSet, apply_deferred, EventWriter, Event}, world::World, system::SystemState};
+use bevy_app::prelude::{Update, App, Plugin};
+use crossbeam::queue::SegQueue;
+use futures::FutureExt;
+use pi_assets::{mgr::{AssetMgr, LoadResult}, asset::{Handle, GarbageEmpty}};
+use pi_async_rt::prelude::AsyncRuntime;
+use pi_atom::Atom;
+use pi_bevy_asset::ShareAssetMgr;
+use pi_bevy_render_plugin::{
+    PiRenderDevice, PiRenderQueue, node::{Node, ParamUsage}, PiSafeAtlasAllocator, SimpleInOut, PiClearOptions, PiRenderGraph, NodeId, GraphError, PiRenderSystemSet, render_cross::GraphId, PiRenderOptions,
+    constant::texture_sampler::*, RenderContext, PiScreenTexture
+};
+use pi_null::Null;
+use pi_window_renderer::WindowRenderer;
+use pi_hal::{runtime::RENDER_RUNTIME, loader::AsyncLoader};
+use pi_hash::XHashMap;
+use pi_render::{rhi::{sampler::{SamplerDesc, EAddressMode, EFilterMode, EAnisotropyClamp}, asset::{TextureRes, ImageTextureDesc}}, asset::TAssetKeyU64, renderer::sampler::SamplerRes, components::view::target_alloc::{ShareTargetView, TargetDescriptor, TextureDescriptor}};
+use pi_share::Share;
+use renderer::{RendererAsync, SpineResource};
+use vertex_buffer::SpineMesh;
+use blit::SpineMsaaTarget;
+use group::{KeySpineGroup, SpineGroupParam, SpineGroupRenderNode};
+use texture::{SpineTextureDesc, ESpineTextureError, SpineTextureStore, checkerboard_rgba8, create_texture_rgba8, create_texture_array_rgba8, create_texture_compressed, write_texture_region};
+use stats::{SpineRenderStats, SpineRendererStats, SpineBudgetStats};
+use shaders::{KeySpineShader, ESpineVertexPacking, SingleSpinePipelinePool};
+use binds::uniform::{SpineUniform, ESpineUniformError};
+use error::{SpineRenderError, SpineRenderErrorEvent, SpineTextureLoadFailEvent};
+use smallvec::SmallVec;
+use wgpu::StoreOp;
+
+
+pub mod binds;
+pub mod bind_groups;
+pub mod shaders;
+pub mod vertex_buffer;
+pub mod renderer;
+pub mod ecs;
+pub mod error;
+pub mod blit;
+pub mod group;
+pub mod stats;
+pub mod texture;
+
+/// Atlas 目标的默认格式
+pub const FORMAT: ColorFormat = ColorFormat::Rgba8Unorm;
+pub const SAMPLER_DESC: SamplerDesc = SamplerDesc {
+    address_mode_u: EAddressMode::Repeat,
+    address_mode_v: EAddressMode::Repeat,
+    address_mode_w: EAddressMode::Repeat,
+    mag_filter: EFilterMode::Linear,
+    min_filter: EFilterMode::Linear,
+    mipmap_filter: EFilterMode::Nearest,
+    compare: None,
+    anisotropy_clamp: EAnisotropyClamp::One,
+    border_color: None,
+};
+
+/// 带 mip 链的纹理使用三线性过滤
+pub const SAMPLER_DESC_MIPMAP: SamplerDesc = SamplerDesc {
+    address_mode_u: EAddressMode::Repeat,
+    address_mode_v: EAddressMode::Repeat,
+    address_mode_w: EAddressMode::Repeat,
+    mag_filter: EFilterMode::Linear,
+    min_filter: EFilterMode::Linear,
+    mipmap_filter: EFilterMode::Linear,
+    compare: None,
+    anisotropy_clamp: EAnisotropyClamp::One,
+    border_color: None,
+};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct KeySpineRenderer(pub Entity);
+impl KeySpineRenderer {
+    pub fn from_f64(val: f64) -> Self {
+        Self(Entity::from_bits(val.to_bits()))
+    }
+}
+
+/// 渲染器的输出目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ESpineTarget {
+    /// 从 Atlas 分配的独立目标
+    Atlas,
+    /// 直接渲染到屏幕
+    Screen,
+    /// 叠加绘制到上游节点输出的目标
+    Upstream,
+}
+
+/// 渲染图中的依赖对象
+#[derive(Debug, Clone)]
+pub enum ESpineGraphNode {
+    /// 按名称指定的节点, 如 WindowRenderer::KEY
+    Name(String),
+    /// 另一个 Spine 渲染器的节点
+    Renderer(KeySpineRenderer),
+}
+
+/// Atlas 目标被重新分配 (首次分配, 尺寸或格式变化) 时发送, 之前取得的输出纹理失效
+#[derive(Debug, Clone, Event)]
+pub struct SpineTargetChangedEvent {
+    pub renderer: KeySpineRenderer,
+    pub width: u32,
+    pub height: u32,
+    pub format: wgpu::TextureFormat,
+    /// 每次重新分配递增
+    pub generation: u64,
+}
+
+/// 渲染器的输出, 由渲染图 build 阶段更新
+#[derive(Clone)]
+pub struct SpineRenderOutput {
+    pub target: ShareTargetView,
+    /// 目标纹理中的有效区域 x, y, w, h
+    pub rect: (u32, u32, u32, u32),
+    /// 与 SpineTargetChangedEvent 的 generation 一致
+    pub generation: u64,
+}
+impl SpineRenderOutput {
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.target.target().colors[0].0
+    }
+}
+
+pub struct SpineRenderNodeParam {
+    render: RendererAsync,
+    width: u32,
+    height: u32,
+    target: ESpineTarget,
+    node: NodeId,
+    sample_count: u32,
+    /// Atlas 目标的格式
+    format: wgpu::TextureFormat,
+    output: Option<SpineRenderOutput>,
+    /// 所属渲染器组, 由组节点统一绘制
+    group: Option<KeySpineGroup>,
+}
+impl SpineRenderNodeParam {
+    pub fn render_mut(&mut self) -> &mut RendererAsync {
+        &mut self.render
+    }
+}
+
+pub struct SpineRenderNode{
+	pub renderer: KeySpineRenderer,
+	rt: Option<ShareTargetView>,
+	/// 上游目标的有效区域
+	valid_rect: Option<(u32, u32, u32, u32)>,
+	msaa: Option<SpineMsaaTarget>,
+	/// 当前 rt 对应的宽, 高, 格式; 不变时复用 rt
+	rt_key: Option<(u32, u32, wgpu::TextureFormat)>,
+	generation: u64,
+}
+
+impl SpineRenderNode {
+	pub fn new(renderer: KeySpineRenderer) -> Self {
+		Self { renderer, rt: None, valid_rect: None, msaa: None, rt_key: None, generation: 0 }
+	}
+	fn prepare_msaa(&mut self, world: &mut World, width: u32, height: u32, sample_count: u32, format: wgpu::TextureFormat) {
+		if sample_count <= 1 {
+			self.msaa = None;
+			return;
+		}
+		if let Some(msaa) = &self.msaa {
+			if msaa.is_match(width, height, sample_count, format) {
+				return;
+			}
+		}
+		let device = world.get_resource::<PiRenderDevice>().unwrap().0.clone();
+		let mut resource = world.get_resource_mut::<SpineResource>().unwrap();
+		self.msaa = Some(SpineMsaaTarget::new(&device, &mut resource.blit, width, height, sample_count, format));
+	}
+	/// 将当前输出写入 SpineRenderContext 供其他系统查询
+	fn publish_output(&self, world: &mut World) {
+		let output = self.rt.as_ref().map(|target| {
+			let rect = target.rect();
+			SpineRenderOutput {
+				target: target.clone(),
+				rect: self.valid_rect.unwrap_or((
+					rect.min.x as u32,
+					rect.min.y as u32,
+					(rect.max.x - rect.min.x) as u32,
+					(rect.max.y - rect.min.y) as u32,
+				)),
+				generation: self.generation,
+			}
+		});
+		if let Some(renderer) = world.get_resource_mut::<SpineRenderContext>().unwrap().list.get_mut(&self.renderer) {
+			renderer.output = output;
+		}
+	}
+}
+
+/// 从 Atlas 分配 width x height 的目标区域
+pub(crate) fn allocate_atlas_target(world: &World, width: u32, height: u32, format: wgpu::TextureFormat) -> ShareTargetView {
+	let temp: Vec<ShareTargetView> = vec![];
+	let atlas_allocator = world.get_resource::<PiSafeAtlasAllocator>().unwrap();
+	let target_type = atlas_allocator.get_or_create_type(
+		TargetDescriptor {
+			colors_descriptor: SmallVec::from_slice(
+				&[
+					TextureDescriptor {
+						mip_level_count: 1,
+						sample_count: 1,
+						dimension: wgpu::TextureDimension::D2,
+						format,
+						usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::RENDER_ATTACHMENT,
+						base_mip_level: 0,
+						base_array_layer: 0,
+						array_layer_count: None,
+						view_dimension: None,
+					}
+				]
+			),
+			need_depth: false,
+			default_width: 2048,
+			default_height: 2048,
+			depth_descriptor: None,
+		}
+	);
+	atlas_allocator.allocate(width, height, target_type, temp.iter())
+}
+
+impl Node for SpineRenderNode {
+    type Input = SimpleInOut;
+
+    type Output = SimpleInOut;
+
+	type BuildParam = ();
+    type RunParam = Res<'static, PiScreenTexture>;
+
+	fn build<'a>(
+        &'a mut self,
+        world: &'a mut World,
+        param: &'a mut SystemState<Self::BuildParam>,
+        context: RenderContext,
+		input: &'a Self::Input,
+        usage: &'a ParamUsage,
+		id: NodeId,
+		from: &'a [NodeId],
+		to: &'a [NodeId],
+    ) -> Result<Self::Output, String> {
+		let spine_ctx = world.get_resource::<SpineRenderContext>().unwrap();
+		let renderer = if let Some(renderer) = spine_ctx.list.get(&self.renderer) {
+			renderer
+		} else {
+			log::warn!("SpineGraph:: None renderer");
+			return Ok(SimpleInOut { target: None, valid_rect: None })
+		};
+
+		// 组成员由组节点绘制并更新输出
+		if renderer.group.is_some() {
+			self.rt = None;
+			self.rt_key = None;
+			self.msaa = None;
+			return Ok(SimpleInOut { target: None, valid_rect: None });
+		}
+
+		if renderer.target == ESpineTarget::Upstream {
+			self.rt_key = None;
+			self.rt = input.target.clone();
+			self.valid_rect = input.valid_rect;
+			if self.rt.is_none() {
+				log::warn!("SpineGraph:: None upstream target");
+			}
+			self.publish_output(world);
+			return Ok(SimpleInOut { target: input.target.clone(), valid_rect: input.valid_rect });
+		}
+
+		if renderer.target == ESpineTarget::Atlas {
+			let format = renderer.format;
+			let (width, height, sample_count) = (renderer.width, renderer.height, renderer.sample_count);
+			if let (Some(target), Some(key)) = (&self.rt, self.rt_key) {
+				if key == (width, height, format) {
+					let target = target.clone();
+					self.prepare_msaa(world, width, height, sample_count, format);
+					self.publish_output(world);
+					return Ok(SimpleInOut { target: Some(target), valid_rect: None });
+				}
+			}
+
+			// 先释放旧区域再分配, 已发布的输出也持有旧区域, 需一并清除
+			self.rt = None;
+			self.publish_output(world);
+			let target = allocate_atlas_target(world, width, height, format);
+			self.rt = Some(target.clone());
+			self.rt_key = Some((width, height, format));
+			self.generation += 1;
+			self.valid_rect = None;
+			self.prepare_msaa(world, width, height, sample_count, format);
+			self.publish_output(world);
+			world.send_event(SpineTargetChangedEvent { renderer: self.renderer, width, height, format, generation: self.generation });
+			Ok(SimpleInOut { target: Some(target), valid_rect: None })
+		} else {
+			Ok(SimpleInOut { target: None, valid_rect: None })
+		}
+	}
+
+    fn run<'a>(
+        &'a mut self,
+        world: &'a bevy_ecs::prelude::World,
+        param: &'a mut bevy_ecs::system::SystemState<Self::RunParam>,
+        _context: pi_bevy_render_plugin::RenderContext,
+        commands: pi_share::ShareRefCell<wgpu::CommandEncoder>,
+        _input: &'a Self::Input,
+        _usage: &'a pi_bevy_render_plugin::node::ParamUsage,
+		_id: NodeId,
+		_from: &[NodeId],
+		_to: &[NodeId],
+    ) -> pi_futures::BoxFuture<'a, Result<(), String>> {
+        let atlas_allocator = world.get_resource::<PiSafeAtlasAllocator>().unwrap();
+        let temp: Vec<ShareTargetView> = vec![];
+        
+        let spine_ctx = world.get_resource::<SpineRenderContext>().unwrap();
+
+        let renderer = if let Some(renderer) = spine_ctx.list.get(&self.renderer) {
+            renderer
+        } else {
+            return async move {
+                log::warn!("SpineGraph:: None renderer");
+                Ok(())
+            }.boxed();
+        };
+        // 组成员由组节点绘制; 未变化的 Atlas 目标保留上次的内容
+        if renderer.group.is_some() || !renderer.render.is_active() {
+            return async move { Ok(()) }.boxed();
+        }
+
+        let screen = param.get(world);
+
+		return Box::pin(
+            async move {
+                
+                let mut encoder = commands.0.as_ref().borrow_mut();
+
+                if renderer.target == ESpineTarget::Screen {
+                    let (view, width, height) = match screen.0.as_ref().and_then(|screen| Some((screen.view.as_ref()?, screen.texture.as_ref()?))) {
+                        Some((view, texture)) => (view, texture.texture.width(), texture.texture.height()),
+                        None => return Ok(()),
+                    };
+
+                    let mut renderpass = encoder.begin_render_pass(
+                        &wgpu::RenderPassDescriptor {
+                            label: Some("SpineScreen"),
+                            color_attachments: &[
+                                Some(
+                                    wgpu::RenderPassColorAttachment {
+                                        view,
+                                        resolve_target: None,
+                                        ops: wgpu::Operations {
+                                            load: wgpu::LoadOp::Load,
+                                            store: StoreOp::Store,
+                                        }
+                                    }
+                                )
+                            ],
+                            depth_stencil_attachment: None,
+                            timestamp_writes: None,
+                            occlusion_query_set: None,
+                        }
+                    );
+                    renderer.render.render_rect(&mut renderpass, (0, 0, width, height), (width, height));
+                    return Ok(());
+                }
+
+
+                let target = match &self.rt {
+                    Some(r) => r,
+                    None => return  Ok(()),
+                };
+
+                if let (ESpineTarget::Atlas, Some(msaa)) = (renderer.target, &self.msaa) {
+                    // 不清屏时保留多重采样纹理中上一帧的内容
+                    let (load, store) = match renderer.render.clear_color() {
+                        Some(color) => (wgpu::LoadOp::Clear(color), StoreOp::Discard),
+                        None => (wgpu::LoadOp::Load, StoreOp::Store),
+                    };
+                    {
+                        let mut renderpass = encoder.begin_render_pass(
+                            &wgpu::RenderPassDescriptor {
+                                label: Some("SpineMsaa"),
+                                color_attachments: &[
+                                    Some(
+                                        wgpu::RenderPassColorAttachment {
+                                            view: &msaa.view,
+                                            resolve_target: Some(&msaa.resolve),
+                                            ops: wgpu::Operations {
+                                                load,
+                                                store,
+                                            }
+                                        }
+                                    )
+                                ],
+                                depth_stencil_attachment: None,
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                            }
+                        );
+                        renderer.render.render_rect(&mut renderpass, (0, 0, msaa.width, msaa.height), (msaa.width, msaa.height));
+                    }
+                    {
+                        let mut renderpass = encoder.begin_render_pass(
+                            &wgpu::RenderPassDescriptor {
+                                label: Some("SpineMsaaResolve"),
+                                color_attachments: &[
+                                    Some(
+                                        wgpu::RenderPassColorAttachment {
+                                            view: &target.target().colors[0].0,
+                                            resolve_target: None,
+                                            ops: wgpu::Operations {
+                                                load: wgpu::LoadOp::Load,
+                                                store: StoreOp::Store,
+                                            }
+                                        }
+                                    )
+                                ],
+                                depth_stencil_attachment: None,
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                            }
+                        );
+                        let rect = target.rect();
+                        let (x, y, w, h) = (
+                            rect.min.x as u32,
+                            rect.min.y as u32,
+                            (rect.max.x - rect.min.x) as u32,
+                            (rect.max.y - rect.min.y) as u32,
+                        );
+                        renderpass.set_viewport(x as f32, y as f32, w as f32, h as f32, 0., 1.);
+                        renderpass.set_scissor_rect(x, y, w, h);
+                        msaa.blit(&mut renderpass);
+                    }
+                    return Ok(());
+                }
+                
+                // 清屏在同一 Pass 内以矩形绘制完成, 不影响 Atlas 中的其他区域
+                {
+                    let mut renderpass = encoder.begin_render_pass(
+                        &wgpu::RenderPassDescriptor {
+                            label: Some("RenderNode"),
+                            color_attachments: &[
+                                Some(
+                                    wgpu::RenderPassColorAttachment {
+                                        view: &target.target().colors[0].0,
+                                        resolve_target: None,
+                                        ops: wgpu::Operations {
+                                            load: wgpu::LoadOp::Load,
+                                            store: StoreOp::Store,
+                                        }
+                                    }
+                                )
+                            ],
+                            depth_stencil_attachment: None,
+                            timestamp_writes: None,
+                            occlusion_query_set: None,
+                        }
+                    );
+                    let rect = target.rect();
+                    let (x, y, w, h) = if let Some(valid_rect) = self.valid_rect {
+                        valid_rect
+                    } else {
+                        (
+                            rect.min.x as u32,
+                            rect.min.y as u32,
+                            (rect.max.x - rect.min.x) as u32,
+                            (rect.max.y - rect.min.y) as u32,
+                        )
+                    };
+            
+                    // log::warn!("SpineGraph DrawList::render: {:?}", renderer.render.drawobjs.list.len());
+                    renderer.render.render_rect(&mut renderpass, (x, y, w, h), (target.target().width, target.target().height));
+                }
+
+                Ok(())
+        	});
+        
+		// let screen = param.get(world);
+
+		// Box::pin(async move {
+		//     if let Some(view) = screen.view() {
+		//         let mut encoder = commands.0.as_ref().borrow_mut();
+		//         let mut renderpass = encoder.begin_render_pass(
+		//             &wgpu::RenderPassDescriptor {
+		//                 label: None,
+		//                 color_attachments: &[
+		//                     Some(
+		//                         wgpu::RenderPassColorAttachment {
+		//                             view: &view,
+		//                             resolve_target: None,
+		//                             ops: wgpu::Operations {
+		//                                 load: wgpu::LoadOp::Load,
+		//                                 store: true,
+		//                             }
+		//                         }
+		//                     )
+		//                 ],
+		//                 depth_stencil_attachment: None,
+		//             }
+		//         );
+				
+		//         // renderpass.set_viewport(x, y, w, h, min_depth, max_depth);
+		//         // renderpass.set_scissor_rect(x as u32, y as u32, w as u32, h as u32);
+		//         // log::warn!("SpineGraph Draws: {:?}", renderer.render.drawobjs.list.len());
+		//         DrawList::render(renderer.render.drawobjs.list.as_slice(), &mut renderpass);
+		//     }
+
+		//     Ok(SimpleInOut { target: None, valid_rect: None })
+		// })
+    }
+}
+
+
+#[derive(Resource)]
+pub struct SpineRenderContext {
+    list: XHashMap<KeySpineRenderer, SpineRenderNodeParam>,
+    groups: XHashMap<KeySpineGroup, SpineGroupParam>,
+}
+impl SpineRenderContext {
+    pub fn new() -> Self {
+        Self { list: XHashMap::default(), groups: XHashMap::default() }
+    }
+    pub fn group(&self, key: KeySpineGroup) -> Option<&SpineGroupParam> {
+        self.groups.get(&key)
+    }
+    /// 组的排布或目标变化时, 所有成员须重新绘制
+    pub fn mark_group_dirty(&mut self, key: KeySpineGroup) {
+        if let Some(group) = self.groups.get(&key) {
+            group.members.iter().for_each(|member| {
+                if let Some(renderer) = self.list.get_mut(member) {
+                    renderer.render.mark_dirty();
+                }
+            });
+        }
+    }
+    pub fn get_mut(&mut self, key: KeySpineRenderer) -> Option<&mut SpineRenderNodeParam> {
+        self.list.get_mut(&key)
+    }
+    /// 渲染器当前的输出纹理与区域; 屏幕目标或尚未分配时为 None
+    pub fn output(&self, key: KeySpineRenderer) -> Option<&SpineRenderOutput> {
+        self.list.get(&key).and_then(|renderer| renderer.output.as_ref())
+    }
+    /// 渲染器记录的纹理及其字节数
+    pub fn textures(&self, key: KeySpineRenderer) -> Option<Vec<(u64, usize)>> {
+        self.list.get(&key).map(|renderer| renderer.render.texture_usage())
+    }
+    /// 渲染器本帧被跳过的绘制及原因
+    pub fn errors(&self, key: KeySpineRenderer) -> Option<&[SpineRenderError]> {
+        self.list.get(&key).map(|renderer| renderer.render.errors())
+    }
+    pub fn create_renderer(&mut self, key: KeySpineRenderer, to_screen: bool) {
+        // self.counter += 1;
+        // let id = self.counter;
+
+        let target = if to_screen { ESpineTarget::Screen } else { ESpineTarget::Atlas };
+        let mut render = SpineRenderNodeParam { render: RendererAsync::new(), width: 128, height: 128, target, node: NodeId::null(), sample_count: 1, format: FORMAT.val(), output: None, group: None };
+        if target == ESpineTarget::Atlas {
+            render.render.set_clear_color(Some(wgpu::Color::TRANSPARENT));
+        }
+        self.list.insert(key, render);
+    }
+}
+
+#[derive(Clone)]
+pub enum ESpineCommand {
+    Create(KeySpineRenderer, String, Option<(u32, u32)>, wgpu::TextureFormat),
+    Dispose(KeySpineRenderer),
+    TextureLoad(Atom),
+    TextureRecord(KeySpineRenderer, Handle<TextureRes>),
+    SamplerRecord(KeySpineRenderer, SamplerDesc, Handle<SamplerRes>),
+    RemoveTextureRecord(KeySpineRenderer, u64),
+    /// 记录纹理对象以便局部更新, 由 spine_texture 等接口在创建纹理时发出
+    TextureSource(u64, std::sync::Arc<wgpu::Texture>),
+    /// 将 RGBA8 数据写入已有纹理的 (x, y, w, h) 区域, 使用该纹理的渲染器重绘
+    TextureRegion(u64, (u32, u32, u32, u32), Vec<u8>),
+    /// 清空绘制与 Uniform; 否则二者保留到下一次 Reset, 无变化的 Atlas 目标不再重绘
+    Reset(KeySpineRenderer),
+    /// Atlas 目标尺寸, 变化后下一帧重新分配并发送 SpineTargetChangedEvent
+    RenderSize(KeySpineRenderer, u32, u32),
+    Shader(KeySpineRenderer, Option<KeySpineShader>),
+    UseTexture(KeySpineRenderer, Option<Handle<TextureRes>>, Option<Handle<SamplerRes>>),
+    /// 记录纹理与采样器, 末尾标记纹理是否为纹理数组 (D2Array 视图)
+    Texture(KeySpineRenderer, u64, Handle<TextureRes>, SamplerDesc, Handle<SamplerRes>, bool),
+    Blend(KeySpineRenderer, bool),
+    BlendMode(KeySpineRenderer, wgpu::BlendFactor, wgpu::BlendFactor),
+    Uniform(KeySpineRenderer, SpineUniform),
+    Draw(KeySpineRenderer, Vec<f32>, Vec<u16>, u32, u32),
+    /// 后续绘制的顶点压缩布局
+    VertexPacking(KeySpineRenderer, ESpineVertexPacking),
+    /// 上传 GPU 蒙皮的常驻网格: 顶点, 索引
+    SkinnedMesh(KeySpineRenderer, u64, Vec<f32>, Vec<u16>),
+    RemoveSkinnedMesh(KeySpineRenderer, u64),
+    /// 后续 Skinned 绘制的骨骼矩阵
+    Bones(KeySpineRenderer, Vec<f32>),
+    DrawSkinned(KeySpineRenderer, u64),
+    Graph(KeySpineRenderer, NodeId),
+    /// 在指定名称的上游节点输出目标上叠加绘制, 格式须与该目标一致
+    RenderOnto(KeySpineRenderer, String, wgpu::TextureFormat),
+    /// 创建渲染器组及其渲染图节点, 成员共用一个该格式的 Atlas 目标
+    CreateGroup(KeySpineGroup, String, wgpu::TextureFormat),
+    DisposeGroup(KeySpineGroup),
+    /// 加入 (Some) 或离开 (None) 渲染器组, 仅 Atlas 目标的渲染器可加入, 组内不支持多重采样
+    JoinGroup(KeySpineRenderer, Option<KeySpineGroup>),
+    /// 渲染器在指定节点之前执行
+    Before(KeySpineRenderer, ESpineGraphNode),
+    /// 渲染器在指定节点之后执行
+    After(KeySpineRenderer, ESpineGraphNode),
+    /// 多重采样数 1/2/4/8, 仅 Atlas 目标生效
+    SampleCount(KeySpineRenderer, u32),
+    /// Atlas 目标的格式, 如 Rgba8UnormSrgb, Rgba16Float, Bgra8Unorm
+    TargetFormat(KeySpineRenderer, wgpu::TextureFormat),
+    /// 绘制前的清屏颜色, None 保留已有内容; Atlas 默认透明, 其他默认不清屏
+    ClearColor(KeySpineRenderer, Option<wgpu::Color>),
+    /// 相对目标区域的视口 x, y, w, h, None 为整个目标区域
+    Viewport(KeySpineRenderer, Option<[f32; 4]>),
+    /// 相对目标区域的裁剪矩形 x, y, w, h, None 不裁剪
+    Scissor(KeySpineRenderer, Option<[u32; 4]>),
+}
+
+
+pub type ActionListSpine = pi_bevy_ecs_extend::action::ActionList<ESpineCommand>;
+
+pub fn sys_spine_cmds(
+    mut cmds: ResMut<ActionListSpine>,
+    mut clearopt: ResMut<PiClearOptions>,
+    mut renderers: ResMut<SpineRenderContext>,
+    renderopt: Res<PiRenderOptions>,
+    mut graphic: ResMut<PiRenderGraph>,
+    mut texloader: ResMut<SpineTextureLoad>,
+    nodes: Query<&GraphId>,
+    mut commands: Commands,
+    mut errors: EventWriter<SpineRenderErrorEvent>,
+    device: Res<PiRenderDevice>,
+    queue: Res<PiRenderQueue>,
+    mut texstore: ResMut<SpineTextureStore>,
+) {
+    clearopt.color.g = 0.;
+    renderers.list.iter_mut().for_each(|(_, v)| {
+        v.render.clear_errors();
+    });
+    let mut list = cmds.drain();
+    // let len = list.len();
+    let mut index = 0;
+    list.drain(..).for_each(|cmd| {
+        index += 1;
+        match cmd {
+            ESpineCommand::Create(id, name, rendersize, format) => {
+                let rendersize = match rendersize {
+                    Some((width, height)) if !ActionSpine::is_valid_render_size(width, height, &device) => {
+                        log::warn!("Spine RenderSize Err {:?}", (width, height));
+                        Some((width.max(1).min(device.0.limits().max_texture_dimension_2d), height.max(1).min(device.0.limits().max_texture_dimension_2d)))
+                    },
+                    _ => rendersize,
+                };
+                ActionSpine::create_spine_renderer(id, rendersize, &mut renderers, format);
+                match ActionSpine::spine_renderer_apply(id, pi_atom::Atom::from(name), rendersize.is_none(), &mut graphic) {
+                    Ok(nodeid) => {
+                        if let Some(renderer) = renderers.list.get_mut(&id) {
+                            renderer.node = nodeid;
+                        }
+                        if let Some(mut cmd) = commands.get_entity(id.0) {
+                            cmd.insert(GraphId(nodeid));
+                        }
+                    },
+                    Err(e) => {
+                        log::warn!("Spine render_graph Err {:?}", e);
+                        if let Some(renderer) = renderers.list.get_mut(&id) {
+                            renderer.render.push_error(SpineRenderError::Graph { message: format!("{:?}", e) });
+                        }
+                    },
+                }
+            },
+            ESpineCommand::Dispose(id_renderer) => {
+                if let Ok(nodeid) = nodes.get(id_renderer.0) {
+                    graphic.remove_node(nodeid.0);
+                }
+                ActionSpine::dispose_spine_renderer(id_renderer, &mut renderers);
+                if let Some(mut cmds) = commands.get_entity(id_renderer.0) {
+                    cmds.despawn();
+                }
+            },
+            ESpineCommand::TextureLoad(key) => {
+                texloader.load(key);
+            },
+            ESpineCommand::TextureRecord(id_renderer, val) => {
+                if let Some(renderer) = renderers.get_mut(id_renderer) {
+                    // log::warn!("Cmd: Texture");
+                    let key_u64 = val.key();
+                    renderer.render_mut().record_texture(*key_u64, val);
+                }
+            },
+            ESpineCommand::SamplerRecord(id_renderer, samplerdesc, val) => {
+                if let Some(renderer) = renderers.get_mut(id_renderer) {
+                    // log::warn!("Cmd: Texture");
+                    renderer.render_mut().record_sampler(samplerdesc, val);
+                }
+            },
+            ESpineCommand::RemoveTextureRecord(id_renderer, key) => {
+                if let Some(renderer) = renderers.get_mut(id_renderer) {
+                    // log::warn!("Cmd: Texture");
+                    renderer.render_mut().remove_texture(key);
+                }
+            },
+            ESpineCommand::Uniform(id, val) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    // log::warn!("Cmd: Uniform");
+                    renderer.render.uniform(val);
+                }
+            },
+            ESpineCommand::Shader(id, val) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    // log::warn!("Cmd: Shader");
+                    renderer.render.shader(val);
+                }
+            },
+            ESpineCommand::UseTexture(id, val, sampler) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    // log::warn!("Cmd: UseTexture");
+                    renderer.render.texture(val, sampler);
+                }
+            },
+            ESpineCommand::Draw(id, vertices, indices, vlen, ilen) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    // log::warn!("Cmd: Draw");
+                    // log::warn!("Cmd Draw: {:?} in {:?}", index, len);
+                    renderer.render.draw(vertices, Some(indices), vlen, ilen, &renderopt);
+                } else {
+                    errors.send(SpineRenderErrorEvent { renderer: id, error: SpineRenderError::MissingRenderer });
+                }
+            },
+            ESpineCommand::VertexPacking(id, packing) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.set_vertex_packing(packing);
+                }
+            },
+            ESpineCommand::SkinnedMesh(id, key, vertices, indices) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.mesh(key, SpineMesh::new(&device.0, &vertices, &indices, KeySpineShader::Skinned.vertices_bytes_per_element()));
+                }
+            },
+            ESpineCommand::RemoveSkinnedMesh(id, key) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.remove_mesh(key);
+                }
+            },
+            ESpineCommand::Bones(id, val) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.bones(val);
+                }
+            },
+            ESpineCommand::DrawSkinned(id, key) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.draw_skinned(key);
+                } else {
+                    errors.send(SpineRenderErrorEvent { renderer: id, error: SpineRenderError::MissingRenderer });
+                }
+            },
+            ESpineCommand::TextureSource(key, texture) => {
+                texstore.insert(key, texture);
+            },
+            ESpineCommand::TextureRegion(key, (x, y, width, height), data) => {
+                let result = match texstore.get(key) {
+                    Some(texture) => write_texture_region(&queue.0, texture, x, y, width, height, &data)
+                        .map_err(|error| SpineRenderError::TextureRegion { key, error }),
+                    None => Err(SpineRenderError::MissingTextureSource { key }),
+                };
+                if let Err(e) = &result {
+                    log::warn!("Spine TextureRegion Err {:?}", e);
+                }
+                renderers.list.values_mut().for_each(|renderer| {
+                    if renderer.render.uses_texture(key) {
+                        match &result {
+                            Ok(_) => renderer.render.mark_dirty(),
+                            Err(e) => renderer.render.push_error(e.clone()),
+                        }
+                    }
+                });
+            },
+            ESpineCommand::Texture(id, key, value, key2, value2, array) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    // log::warn!("Cmd: Texture");
+                    renderer.render.record_texture(key, value);
+                    renderer.render.record_texture_array(key, array);
+                    renderer.render.record_sampler(key2, value2);
+                }
+            },
+            ESpineCommand::RenderSize(id, width, height) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    if ActionSpine::is_valid_render_size(width, height, &device) {
+                        renderer.width = width;
+                        renderer.height = height;
+                        renderer.render.mark_dirty();
+                        if let Some(group) = renderer.group {
+                            renderers.mark_group_dirty(group);
+                        }
+                    } else {
+                        log::warn!("Spine RenderSize Err {:?}", (width, height));
+                    }
+                }
+            },
+            ESpineCommand::Reset(id) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    // log::warn!("Cmd Reset: {:?} in {:?}", index, len);
+                    renderer.render.reset();
+                }
+            },
+            ESpineCommand::Blend(id, val) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.blend(val);
+                }
+            }
+            ,
+            ESpineCommand::BlendMode(id, val0, val1) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.blend_mode(val0, val1);
+                }
+            },
+            ESpineCommand::Graph(id, node) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.node = node;
+                }
+                commands.entity(id.0).insert(GraphId(node));
+            },
+            ESpineCommand::SampleCount(id, count) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    if ActionSpine::is_valid_sample_count(count, renderer.format, &device) {
+                        renderer.sample_count = count;
+                        renderer.render.mark_dirty();
+                        renderer.render.sample_count = if renderer.target == ESpineTarget::Atlas && renderer.group.is_none() { count } else { 1 };
+                    } else {
+                        log::warn!("Spine SampleCount Err {:?}", count);
+                        renderer.render.push_error(SpineRenderError::SampleCount { count, format: renderer.format });
+                    }
+                }
+            },
+            ESpineCommand::Viewport(id, viewport) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    match viewport {
+                        Some(viewport) if viewport.iter().any(|v| !v.is_finite()) || viewport[2] <= 0. || viewport[3] <= 0. => {
+                            log::warn!("Spine Viewport Err {:?}", viewport);
+                        },
+                        Some(viewport) => renderer.render.viewport(&viewport),
+                        None => renderer.render.viewport(&[]),
+                    }
+                }
+            },
+            ESpineCommand::Scissor(id, scissor) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.scissor(scissor);
+                }
+            },
+            ESpineCommand::ClearColor(id, color) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.render.set_clear_color(color);
+                }
+            },
+            ESpineCommand::TargetFormat(id, format) => {
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    if !ActionSpine::is_valid_target_format(format, &device) {
+                        log::warn!("Spine TargetFormat Err {:?}", format);
+                        renderer.render.push_error(SpineRenderError::TargetFormat(format));
+                    } else if !ActionSpine::is_valid_sample_count(renderer.sample_count, format, &device) {
+                        log::warn!("Spine TargetFormat Err {:?}", (format, renderer.sample_count));
+                        renderer.render.push_error(SpineRenderError::SampleCount { count: renderer.sample_count, format });
+                    } else {
+                        renderer.format = format;
+                        renderer.render.mark_dirty();
+                        if renderer.target == ESpineTarget::Atlas && renderer.group.is_none() {
+                            renderer.render.target_format = format;
+                        }
+                    }
+                }
+            },
+            ESpineCommand::RenderOnto(id, before, format) => {
+                ActionSpine::spine_group_join(id, None, &mut renderers);
+                if let Some(renderer) = renderers.list.get_mut(&id) {
+                    renderer.target = ESpineTarget::Upstream;
+                    renderer.render.mark_dirty();
+                    renderer.render.set_clear_color(None);
+                    renderer.render.target_format = format;
+                    renderer.render.sample_count = 1;
+                    if let Err(e) = graphic.add_depend(before, renderer.node) {
+                        log::warn!("Spine render_graph depend Err {:?}", e);
+                        renderer.render.push_error(SpineRenderError::Graph { message: format!("{:?}", e) });
+                    }
+                }
+            },
+            ESpineCommand::CreateGroup(id, name, format) => {
+                renderers.groups.insert(id, SpineGroupParam::new(format));
+                match graphic.add_node(name, SpineGroupRenderNode::new(id), NodeId::null()) {
+                    Ok(nodeid) => {
+                        if let Some(group) = renderers.groups.get_mut(&id) {
+                            group.node = nodeid;
+                        }
+                    },
+                    Err(e) => {
+                        log::warn!("Spine render_graph Err {:?}", e);
+                        errors.send(SpineRenderErrorEvent { renderer: KeySpineRenderer(id.0), error: SpineRenderError::Graph { message: format!("{:?}", e) } });
+                    },
+                }
+            },
+            ESpineCommand::DisposeGroup(id) => {
+                // 成员离开组, 恢复各自的格式与多重采样数并重绘
+                let members = renderers.groups.get(&id).map(|group| group.members.clone()).unwrap_or_default();
+                members.into_iter().for_each(|key| {
+                    ActionSpine::spine_group_join(key, None, &mut renderers);
+                });
+                if let Some(group) = renderers.groups.remove(&id) {
+                    if !group.node.is_null() {
+                        graphic.remove_node(group.node);
+                    }
+                }
+            },
+            ESpineCommand::JoinGroup(id, group) => {
+                ActionSpine::spine_group_join(id, group, &mut renderers);
+            },
+            ESpineCommand::Before(id, other) => {
+                ActionSpine::spine_graph_depend(id, other, true, &mut renderers, &mut graphic, &mut errors);
+            },
+            ESpineCommand::After(id, other) => {
+                ActionSpine::spine_graph_depend(id, other, false, &mut renderers, &mut graphic, &mut errors);
+            },
+        }
+    })
+}
+
+pub fn sys_spine_render_apply(
+    mut renderers: ResMut<SpineRenderContext>,
+    mut resource: ResMut<SpineResource>,
+    device: Res<PiRenderDevice>,
+    queue: Res<PiRenderQueue>,
+    asset_samplers: Res<ShareAssetMgr<SamplerRes>>,
+    asset_textures: Res<ShareAssetMgr<TextureRes>>,
+    mut errors: EventWriter<SpineRenderErrorEvent>,
+    mut stats: ResMut<SpineRenderStats>,
+    cfg: Res<SpineAssetConfig>,
+    mut texstore: ResMut<SpineTextureStore>,
+) {
+    // log::warn!("Apply: {:?}", renderers.list.len());
+    // 只有 Atlas 目标 (含组成员) 的内容可跨帧保留
+    let rings = &mut *resource;
+    renderers.list.iter_mut().for_each(|(_, v)| {
+        v.render.evict(cfg.texture_evict_frames);
+        v.render.prepare(v.target == ESpineTarget::Atlas);
+        v.render.uniforms(&mut rings.uniforms, &mut rings.bones);
+    });
+    // 纹理资源已从 AssetMgr 释放且不再被任何渲染器引用时, 释放其纹理对象
+    texstore.retain(|key| asset_textures.contains_key(&key) || renderers.list.values().any(|v| v.render.uses_texture(key)));
+    resource.uniforms.prepare(&device);
+    resource.bones.prepare(&device);
+    renderers.list.iter_mut().for_each(|(k, v)| {
+        v.render.drawlist(&device, &queue, &mut resource, &asset_samplers, &asset_textures);
+        v.render.errors().iter().for_each(|error| {
+            errors.send(SpineRenderErrorEvent { renderer: *k, error: error.clone() });
+        });
+    });
+
+    let stats = &mut *stats;
+    stats.frame += 1;
+    stats.total = Default::default();
+    stats.renderers.clear();
+    renderers.list.iter().for_each(|(k, v)| {
+        stats.total.add(v.render.stats());
+        stats.renderers.insert(*k, SpineRendererStats {
+            frame: *v.render.stats(),
+            active: v.render.is_active(),
+            target: v.output.as_ref().map(|output| (output.rect.2, output.rect.3)),
+            textures: v.render.textures.len(),
+            texture_bytes: v.render.texture_usage().iter().map(|(_, bytes)| *bytes).sum(),
+        });
+    });
+    stats.budgets = SpineBudgetStats {
+        uniform_used: resource.uniforms.used(),
+        uniform_capacity: resource.uniforms.capacity(),
+        uniform_budget: cfg.uniform_buffer,
+        vertex_arena_used: resource.verticeallocator.used(),
+        vertex_arena_capacity: resource.verticeallocator.capacity(),
+        vertex_arena_budget: cfg.vertex_arena,
+        index_arena_used: resource.indicesallocator.used(),
+        index_arena_capacity: resource.indicesallocator.capacity(),
+        index_arena_budget: cfg.index_arena,
+        bind_group_cache: resource.bind_group_cache_size(),
+        bind_group_budget: cfg.bind_group.0,
+        pipeline_cache: resource.pipeline_cache_size(),
+        pipeline_budget: SingleSpinePipelinePool::CACHE_CAPACITY,
+        texture_cache: asset_textures.size(),
+        texture_capacity: asset_textures.get_capacity(),
+        sampler_cache: asset_samplers.size(),
+        sampler_capacity: asset_samplers.get_capacity(),
+    };
+
+    resource.uniforms.upload(&queue);
+    resource.bones.upload(&queue);
+    resource.verticeallocator.upload(&queue);
+    resource.indicesallocator.upload(&queue);
+}
+
+// pub trait TInterfaceSpine: TShell {
+//     fn create_spine_renderer(&mut self, name: Atom, next_node: Option<Atom>) -> KeySpineRenderer;
+//     fn dispose_spine_renderer(&mut self, id_renderer: KeySpineRenderer) -> &mut Self;
+//     fn spine_reset(&mut self, id_renderer: KeySpineRenderer) -> &mut Self;
+//     fn spine_uniform(&mut self, id_renderer: KeySpineRenderer, value: &[f32]) -> &mut Self;
+//     fn spine_shader(&mut self, id_renderer: KeySpineRenderer, value: KeySpineShader) -> &mut Self;
+//     fn spine_use_texture(&mut self, id_renderer: KeySpineRenderer, value: u64) -> &mut Self;
+//     fn spine_draw(&mut self, id_renderer: KeySpineRenderer, vertices: &[f32], indices: &[u16], vlen: u32, ilen: u32) -> &mut Self;
+//     fn spine_texture(&mut self, id_renderer: KeySpineRenderer, key: Atom, data: &[u8], width: u32, height: u32) -> &mut Self;
+// }
+
+pub struct ActionSpine;
+impl ActionSpine {
+    /// 仅接受格式保证支持的采样数 (不依赖适配器特有的格式能力), 2 与 8 在 WebGL2/GLES 等平台上不保证可用
+    pub fn is_valid_sample_count(count: u32, format: wgpu::TextureFormat, device: &PiRenderDevice) -> bool {
+        format.guaranteed_format_features(device.0.features()).flags.sample_count_supported(count)
+    }
+    /// 目标须可作为渲染附件、可混合 (管线混合状态) 且可过滤采样 (多重采样写回及上游读取)
+    pub fn is_valid_target_format(format: wgpu::TextureFormat, device: &PiRenderDevice) -> bool {
+        let features = format.guaranteed_format_features(device.0.features());
+        features.allowed_usages.contains(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+            && features.flags.contains(wgpu::TextureFormatFeatureFlags::BLENDABLE | wgpu::TextureFormatFeatureFlags::FILTERABLE)
+    }
+    /// 尺寸须非零且不超过设备的最大纹理尺寸
+    pub fn is_valid_render_size(width: u32, height: u32, device: &PiRenderDevice) -> bool {
+        let max = device.0.limits().max_texture_dimension_2d;
+        width > 0 && height > 0 && width <= max && height <= max
+    }
+    pub fn create_spine_renderer(
+        id: KeySpineRenderer,
+        rendersize: Option<(u32, u32)>,
+        ctx: &mut SpineRenderContext,
+        final_render_format: wgpu::TextureFormat,
+    ) {
+        ctx.create_renderer(id, rendersize.is_none());
+        match rendersize {
+            Some(rendersize) => {
+                let format = ctx.list.get_mut(&id).unwrap().format;
+                ctx.list.get_mut(&id).unwrap().render.target_format = format;
+                ctx.list.get_mut(&id).unwrap().width = rendersize.0;
+                ctx.list.get_mut(&id).unwrap().height = rendersize.1;
+            },
+            None => {
+                ctx.list.get_mut(&id).unwrap().render.target_format = final_render_format;
+            },
+        }
+    }
+
+    pub fn spine_renderer_apply(
+        id: KeySpineRenderer,
+        name: Atom,
+        to_screen: bool,
+        render_graph: &mut PiRenderGraph,
+    ) -> Result<NodeId, GraphError> {
+        let key = String::from(name.as_str());
+        match render_graph.add_node(key.clone(), SpineRenderNode::new(id), NodeId::null()) {
+            Ok(v) => {
+                // 直接渲染到屏幕时, 位于窗口清屏与窗口渲染之间
+                if to_screen {
+                    if let Err(e) = render_graph.add_depend(WindowRenderer::CLEAR_KEY, key.clone()) {
+                        log::warn!("Spine render_graph depend Err {:?}", e);
+                    }
+                    if let Err(e) = render_graph.add_depend(key, WindowRenderer::KEY) {
+                        log::warn!("Spine render_graph depend Err {:?}", e);
+                    }
+                }
+        
+                // render_graph.dump_graphviz();
+                Ok(v)
+            },
+            Err(e) => {
+                Err(e)
+            },
+        }
+    }
+
+    /// 添加 id 与 other 之间的执行顺序; 失败时记录为渲染器的 SpineRenderError::Graph
+    pub fn spine_graph_depend(
+        id: KeySpineRenderer,
+        other: ESpineGraphNode,
+        before: bool,
+        ctx: &mut SpineRenderContext,
+        render_graph: &mut PiRenderGraph,
+        errors: &mut EventWriter<SpineRenderErrorEvent>,
+    ) {
+        let node = match ctx.list.get(&id) {
+            Some(renderer) if !renderer.node.is_null() => renderer.node,
+            _ => {
+                errors.send(SpineRenderErrorEvent { renderer: id, error: SpineRenderError::MissingRenderer });
+                return;
+            },
+        };
+        let result = match other {
+            ESpineGraphNode::Name(name) => {
+                if before { render_graph.add_depend(node, name) } else { render_graph.add_depend(name, node) }
+            },
+            ESpineGraphNode::Renderer(other) => {
+                match ctx.list.get(&other) {
+                    Some(renderer) if !renderer.node.is_null() => {
+                        if before { render_graph.add_depend(node, renderer.node) } else { render_graph.add_depend(renderer.node, node) }
+                    },
+                    _ => {
+                        errors.send(SpineRenderErrorEvent { renderer: other, error: SpineRenderError::MissingRenderer });
+                        return;
+                    },
+                }
+            },
+        };
+        if let Err(e) = result {
+            log::warn!("Spine render_graph depend Err {:?}", e);
+            if let Some(renderer) = ctx.list.get_mut(&id) {
+                renderer.render.push_error(SpineRenderError::Graph { message: format!("{:?}", e) });
+            }
+        }
+    }
+
+    pub fn spine_before(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        other: ESpineGraphNode,
+    ) {
+        cmds.push(ESpineCommand::Before(id_renderer, other));
+    }
+
+    pub fn spine_after(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        other: ESpineGraphNode,
+    ) {
+        cmds.push(ESpineCommand::After(id_renderer, other));
+    }
+
+    pub fn dispose_spine_renderer(
+        id_renderer: KeySpineRenderer,
+        ctx: &mut SpineRenderContext,
+    ) {
+        ActionSpine::spine_group_join(id_renderer, None, ctx);
+        ctx.list.remove(&id_renderer);
+    }
+
+    pub fn spine_group_join(
+        id_renderer: KeySpineRenderer,
+        group: Option<KeySpineGroup>,
+        ctx: &mut SpineRenderContext,
+    ) {
+        let renderer = if let Some(renderer) = ctx.list.get_mut(&id_renderer) {
+            renderer
+        } else {
+            return;
+        };
+        renderer.render.mark_dirty();
+        let old = renderer.group.take();
+        if let Some(old) = old {
+            if let Some(old) = ctx.groups.get_mut(&old) {
+                old.members.retain(|key| *key != id_renderer);
+            }
+            renderer.output = None;
+            renderer.render.target_format = renderer.format;
+            renderer.render.sample_count = renderer.sample_count;
+        }
+        if let Some(key) = group {
+            match ctx.groups.get_mut(&key) {
+                Some(group) if renderer.target == ESpineTarget::Atlas => {
+                    group.members.push(id_renderer);
+                    renderer.group = Some(key);
+                    renderer.render.target_format = group.format;
+                    renderer.render.sample_count = 1;
+                },
+                _ => {
+                    log::warn!("Spine JoinGroup Err {:?}", (id_renderer, key));
+                },
+            }
+        }
+        if let Some(old) = old {
+            ctx.mark_group_dirty(old);
+        }
+        if let Some(key) = group {
+            ctx.mark_group_dirty(key);
+        }
+    }
+
+    pub fn create_spine_group(
+        cmds: &mut ActionListSpine,
+        id_group: KeySpineGroup,
+        name: &str,
+        format: wgpu::TextureFormat,
+    ) {
+        cmds.push(ESpineCommand::CreateGroup(id_group, String::from(name), format));
+    }
+
+    pub fn dispose_spine_group(
+        cmds: &mut ActionListSpine,
+        id_group: KeySpineGroup,
+    ) {
+        cmds.push(ESpineCommand::DisposeGroup(id_group));
+    }
+
+    pub fn spine_join_group(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        group: Option<KeySpineGroup>,
+    ) {
+        cmds.push(ESpineCommand::JoinGroup(id_renderer, group));
+    }
+
+    /// 校验后记录, 存在 NaN/无穷或无效遮罩模式时不记录
+    pub fn spine_uniform(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        value: SpineUniform,
+    ) -> Result<(), ESpineUniformError> {
+        value.validate()?;
+        cmds.push(ESpineCommand::Uniform(id_renderer, value));
+        Ok(())
+    }
+
+    /// 旧接口: 24 个 f32 (u_projTrans, u_maskflag, u_visibility)
+    pub fn spine_uniform_raw(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        value: &[f32],
+    ) -> Result<(), ESpineUniformError> {
+        let value = SpineUniform::from_slice(value)?;
+        cmds.push(ESpineCommand::Uniform(id_renderer, value));
+        Ok(())
+    }
+
+    pub fn spine_shader(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        value: KeySpineShader,
+    ) {
+        cmds.push(ESpineCommand::Shader(id_renderer, Some(value)));
+    }
+
+    pub fn spine_use_texture(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        value: Handle<TextureRes>,
+        sampler: Handle<SamplerRes>,
+    ) {
+        cmds.push(ESpineCommand::UseTexture(id_renderer, Some(value), Some(sampler)));
+    }
+
+    pub fn spine_draw(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        vertices: &[f32],
+        indices: &[u16],
+        vlen: u32,
+        ilen: u32,
+    ) {
+        cmds.push(ESpineCommand::Draw(id_renderer, vertices.to_vec(), indices.to_vec(), vlen, ilen));
+    }
+
+    /// 后续 Draw 上传时使用的顶点压缩布局, 不影响已记录的绘制;
+    /// Unorm16Uv 下 uv 超出 [0, 1] 的 Draw (如 Repeat 平铺) 改用 Float16Uv
+    pub fn spine_vertex_packing(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        packing: ESpineVertexPacking,
+    ) {
+        cmds.push(ESpineCommand::VertexPacking(id_renderer, packing));
+    }
+
+    /// 上传 GPU 蒙皮的常驻网格, 每个顶点 26 个 f32, 布局见 KeySpineShader::Skinned
+    pub fn spine_skinned_mesh(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        key_mesh: u64,
+        vertices: &[f32],
+        indices: &[u16],
+    ) {
+        cmds.push(ESpineCommand::SkinnedMesh(id_renderer, key_mesh, vertices.to_vec(), indices.to_vec()));
+    }
+
+    pub fn spine_remove_skinned_mesh(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        key_mesh: u64,
+    ) {
+        cmds.push(ESpineCommand::RemoveSkinnedMesh(id_renderer, key_mesh));
+    }
+
+    /// 每个骨骼 8 个 f32: a, b, worldX, 0, c, d, worldY, 0
+    pub fn spine_bones(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        bones: &[f32],
+    ) {
+        cmds.push(ESpineCommand::Bones(id_renderer, bones.to_vec()));
+    }
+
+    /// 以当前 Uniform、骨骼矩阵与纹理绘制常驻网格
+    pub fn spine_draw_skinned(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        key_mesh: u64,
+    ) {
+        cmds.push(ESpineCommand::DrawSkinned(id_renderer, key_mesh));
+    }
+
+    pub fn spine_texture(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        key: &str,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        device: & PiRenderDevice,
+        queue: & PiRenderQueue,
+        asset_textures: & ShareAssetMgr<TextureRes>,
+        asset_samplers: & ShareAssetMgr<SamplerRes>,
+    ) {
+        Self::spine_texture_with_desc(cmds, id_renderer, key, data, width, height, &SpineTextureDesc::default(), device, queue, asset_textures, asset_samplers);
+    }
+
+    /// 按选项上传纹理, 如生成 mip 链
+    pub fn spine_texture_with_desc(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        key: &str,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        desc: &SpineTextureDesc,
+        device: & PiRenderDevice,
+        queue: & PiRenderQueue,
+        asset_textures: & ShareAssetMgr<TextureRes>,
+        asset_samplers: & ShareAssetMgr<SamplerRes>,
+    ) {
+
+        let key_u64 = key.asset_u64();
+        let texture = if let Some(textureres) = asset_textures.get(&key_u64) {
+            textureres
+        } else {
+            let (textureres, texture) = create_texture_rgba8(&device.0, &queue.0, data, width, height, desc);
+            
+            if let Ok(textureres) = asset_textures.insert(key_u64, textureres) {
+                cmds.push(ESpineCommand::TextureSource(key_u64, std::sync::Arc::new(texture)));
+                textureres
+            } else {
+                return;
+            }
+        };
+
+        let samplerdesc = desc.sampler.clone().unwrap_or(if desc.mipmap { SAMPLER_DESC_MIPMAP.clone() } else { SAMPLER_DESC.clone() });
+
+        let sampler = if let Some(sampler) = Self::sampler(&samplerdesc, device, asset_samplers) {
+            sampler
+        } else {
+            return;
+        };
+
+        cmds.push(ESpineCommand::Texture(id_renderer, key_u64, texture, samplerdesc, sampler, false));
+    }
+
+    /// 创建品红棋盘格占位纹理及 Nearest 采样器
+    pub fn fallback_texture(
+        device: & PiRenderDevice,
+        queue: & PiRenderQueue,
+        asset_textures: & ShareAssetMgr<TextureRes>,
+        asset_samplers: & ShareAssetMgr<SamplerRes>,
+    ) -> Option<(Handle<TextureRes>, Handle<SamplerRes>)> {
+        let key_u64 = "spine_fallback_texture".asset_u64();
+        let texture = if let Some(textureres) = asset_textures.get(&key_u64) {
+            textureres
+        } else {
+            let (textureres, _) = create_texture_rgba8(&device.0, &queue.0, &checkerboard_rgba8(64, 8), 64, 64, &SpineTextureDesc::default());
+            asset_textures.insert(key_u64, textureres).ok()?
+        };
+        let mut samplerdesc = SAMPLER_DESC.clone();
+        samplerdesc.mag_filter = EFilterMode::Nearest;
+        samplerdesc.min_filter = EFilterMode::Nearest;
+        let sampler = Self::sampler(&samplerdesc, device, asset_samplers)?;
+        Some((texture, sampler))
+    }
+
+    /// 将同尺寸的多页上传为纹理数组, 供 TwoColoredTexturedArray 使用; 顶点中的页序号为 pages 中的下标
+    pub fn spine_texture_array(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        key: &str,
+        pages: &[&[u8]],
+        width: u32,
+        height: u32,
+        desc: &SpineTextureDesc,
+        device: & PiRenderDevice,
+        queue: & PiRenderQueue,
+        asset_textures: & ShareAssetMgr<TextureRes>,
+        asset_samplers: & ShareAssetMgr<SamplerRes>,
+    ) -> Result<(), ESpineTextureError> {
+
+        let key_u64 = key.asset_u64();
+        let texture = if let Some(textureres) = asset_textures.get(&key_u64) {
+            textureres
+        } else {
+            let (textureres, _) = create_texture_array_rgba8(&device.0, &queue.0, pages, width, height, desc)?;
+
+            if let Ok(textureres) = asset_textures.insert(key_u64, textureres) {
+                textureres
+            } else {
+                return Ok(());
+            }
+        };
+
+        let samplerdesc = desc.sampler.clone().unwrap_or(if desc.mipmap { SAMPLER_DESC_MIPMAP.clone() } else { SAMPLER_DESC.clone() });
+
+        let sampler = if let Some(sampler) = Self::sampler(&samplerdesc, device, asset_samplers) {
+            sampler
+        } else {
+            return Ok(());
+        };
+
+        cmds.push(ESpineCommand::Texture(id_renderer, key_u64, texture, samplerdesc, sampler, true));
+        Ok(())
+    }
+
+    /// 从 ShareAssetMgr<SamplerRes> 取得或创建采样器
+    pub fn sampler(
+        samplerdesc: &SamplerDesc,
+        device: & PiRenderDevice,
+        asset_samplers: & ShareAssetMgr<SamplerRes>,
+    ) -> Option<Handle<SamplerRes>> {
+        if let Some(sampler) = asset_samplers.get(samplerdesc) {
+            Some(sampler)
+        } else {
+            asset_samplers.insert(samplerdesc.clone(), SamplerRes::new(&device, samplerdesc)).ok()
+        }
+    }
+
+    /// 更新 key 对应纹理 (须由 spine_texture 创建, 未压缩) 的一个区域, data 为 RGBA8
+    pub fn spine_texture_region(
+        cmds: &mut ActionListSpine,
+        key: &str,
+        rect: (u32, u32, u32, u32),
+        data: Vec<u8>,
+    ) {
+        cmds.push(ESpineCommand::TextureRegion(key.asset_u64(), rect, data));
+    }
+
+    /// 上传 KTX2/DDS 压缩纹理 (BC3/BC7, ETC2, ASTC); 设备不支持该格式时在 CPU 上解码
+    pub fn spine_texture_compressed(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        key: &str,
+        data: &[u8],
+        desc: &SpineTextureDesc,
+        device: & PiRenderDevice,
+        queue: & PiRenderQueue,
+        asset_textures: & ShareAssetMgr<TextureRes>,
+        asset_samplers: & ShareAssetMgr<SamplerRes>,
+    ) -> Result<(), ESpineTextureError> {
+        let key_u64 = key.asset_u64();
+        // 已缓存的纹理无法得知上传的层数, 按 desc.mipmap 选择采样器
+        let (texture, levels) = if let Some(textureres) = asset_textures.get(&key_u64) {
+            (textureres, 1)
+        } else {
+            let (textureres, texture, levels) = create_texture_compressed(&device.0, &queue.0, data, desc)?;
+
+            if let Ok(textureres) = asset_textures.insert(key_u64, textureres) {
+                cmds.push(ESpineCommand::TextureSource(key_u64, std::sync::Arc::new(texture)));
+                (textureres, levels)
+            } else {
+                return Ok(());
+            }
+        };
+
+        let mipmap = desc.mipmap || levels > 1;
+        let samplerdesc = desc.sampler.clone().unwrap_or(if mipmap { SAMPLER_DESC_MIPMAP.clone() } else { SAMPLER_DESC.clone() });
+
+        let sampler = if let Some(sampler) = Self::sampler(&samplerdesc, device, asset_samplers) {
+            sampler
+        } else {
+            return Ok(());
+        };
+
+        cmds.push(ESpineCommand::Texture(id_renderer, key_u64, texture, samplerdesc, sampler, false));
+        Ok(())
+    }
+
+    /// 绘制到名为 before 的节点输出目标上, 不分配独立目标
+    pub fn spine_render_onto(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        before: &str,
+        format: wgpu::TextureFormat,
+    ) {
+        cmds.push(ESpineCommand::RenderOnto(id_renderer, String::from(before), format));
+    }
+
+    pub fn spine_viewport(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        viewport: Option<[f32; 4]>,
+    ) {
+        cmds.push(ESpineCommand::Viewport(id_renderer, viewport));
+    }
+
+    pub fn spine_scissor(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        scissor: Option<[u32; 4]>,
+    ) {
+        cmds.push(ESpineCommand::Scissor(id_renderer, scissor));
+    }
+
+    pub fn spine_clear_color(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        color: Option<wgpu::Color>,
+    ) {
+        cmds.push(ESpineCommand::ClearColor(id_renderer, color));
+    }
+
+    pub fn spine_target_format(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        format: wgpu::TextureFormat,
+    ) {
+        cmds.push(ESpineCommand::TargetFormat(id_renderer, format));
+    }
+
+    pub fn spine_sample_count(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        count: u32,
+    ) {
+        cmds.push(ESpineCommand::SampleCount(id_renderer, count));
+    }
+
+    pub fn spine_reset(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+    ) {
+        cmds.push(ESpineCommand::Reset(id_renderer));
+    }
+
+    pub fn spine_blend(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        value: bool,
+    ) {
+        cmds.push(ESpineCommand::Blend(id_renderer, value));
+    }
+
+    pub fn spine_blend_mode(
+        cmds: &mut ActionListSpine,
+        id_renderer: KeySpineRenderer,
+        src: wgpu::BlendFactor,
+        dst: wgpu::BlendFactor,
+    ) {
+        cmds.push(ESpineCommand::BlendMode(id_renderer, src, dst));
+    }
+}
+
+
+#[derive(Resource, Default)]
+pub struct SpineTextureLoad {
+    pub success: Share<SegQueue<(Atom, Handle<TextureRes>)>>,
+    pub fail: Share<SegQueue<(Atom, String)>>,
+    pub list: Vec<Atom>,
+    /// 各纹理已重试的次数, 加载成功后清除
+    retries: XHashMap<Atom, u32>,
+    /// 异步加载成功的纹理, 用于清除重试次数
+    loaded: Share<SegQueue<Atom>>,
+    /// 等待重试的纹理及其重试的帧
+    pending: Vec<(Atom, u64)>,
+    frame: u64,
+}
+impl SpineTextureLoad {
+    pub fn load(&mut self, key: Atom) {
+        self.retries.remove(&key);
+        self.pending.retain(|(pending, _)| *pending != key);
+        self.list.push(key)
+    }
+}
+
+/// 处理加载失败: 未超过 SpineAssetConfig::texture_retry 时延迟后重新加载, 否则发送 SpineTextureLoadFailEvent
+fn sys_spine_texture_load_fail(
+    mut loader: ResMut<SpineTextureLoad>,
+    cfg: Res<SpineAssetConfig>,
+    mut events: EventWriter<SpineTextureLoadFailEvent>,
+) {
+    let loader = &mut *loader;
+    while let Some(key) = loader.loaded.pop() {
+        loader.retries.remove(&key);
+    }
+    loader.frame += 1;
+    let frame = loader.frame;
+    let list = &mut loader.list;
+    loader.pending.retain(|(key, due)| {
+        if *due <= frame {
+            list.push(key.clone());
+            false
+        } else {
+            true
+        }
+    });
+
+    while let Some((key, error)) = loader.fail.pop() {
+        let retries = loader.retries.get(&key).cloned().unwrap_or(0);
+        if retries < cfg.texture_retry {
+            log::warn!("Spine TextureLoad Retry {:?} {:?}", key, error);
+            loader.retries.insert(key.clone(), retries + 1);
+            // 每次重试的间隔加倍
+            let delay = (cfg.texture_retry_delay as u64).saturating_mul(1u64.checked_shl(retries).unwrap_or(u64::MAX));
+            loader.pending.push((key, frame.saturating_add(delay)));
+        } else {
+            loader.retries.remove(&key);
+            events.send(SpineTextureLoadFailEvent { key, error, attempts: retries + 1 });
+        }
+    }
+}
+
+
+fn sys_spine_texture_load(
+    mut loader: ResMut<SpineTextureLoad>,
+    device: Res<PiRenderDevice>,
+    queue: Res<PiRenderQueue>,
+    texture_assets_mgr: Res<ShareAssetMgr<TextureRes>>,
+) {
+    let mut list = replace(&mut loader.list, vec![]);
+    list.drain(..).for_each(|k| {
+        let result = AssetMgr::load(&texture_assets_mgr, &(k.asset_u64()));
+        match result {
+            LoadResult::Ok(r) => {
+                loader.retries.remove(&k);
+                loader.success.push((k, r));
+            }
+            ,
+            _ => {
+                let success = loader.success.clone();
+                let fail = loader.fail.clone();
+                let loaded = loader.loaded.clone();
+                let device = device.0.clone();
+                let queue = queue.0.clone();
+    
+                RENDER_RUNTIME
+                    .spawn(async move {
+                        let desc = ImageTextureDesc {
+                            url: &k,
+                            device: &device,
+                            queue: &queue,
+                        };
+        
+                        let r = TextureRes::async_load(desc, result).await;
+                        match r {
+                            Ok(r) => {
+                                loaded.push(k.clone());
+                                success.push((k, r));
+                            }
+                            Err(e) => {
+                                fail.push((k, format!("load image fail, {:?}", e)));
+                            }
+                        };
+                    })
+                    .unwrap();
+            }
+        }
+    });
+}
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct SpineSystemSet;
+
+#[derive(Clone, Resource)]
+pub struct SpineAssetConfig {
+    pub vertex_buffer: (usize, usize),
+    /// Uniform 环形 Buffer 的初始字节数, 不足时按倍数扩大
+    pub uniform_buffer: u32,
+    /// 蒙皮骨骼矩阵环形 Buffer 的初始字节数
+    pub bone_buffer: u32,
+    pub bind_group: (usize, usize),
+    /// 顶点数据 Buffer 块的默认字节数, 单次数据更大时按数据大小创建
+    pub vertex_arena: u32,
+    /// 索引数据 Buffer 块的默认字节数
+    pub index_arena: u32,
+    /// 记录的纹理与采样器超过该帧数未被使用时释放, 0 为不释放
+    pub texture_evict_frames: u32,
+    /// 纹理加载失败后的重试次数
+    pub texture_retry: u32,
+    /// 首次重试前等待的帧数, 之后每次重试加倍
+    pub texture_retry_delay: u32,
+    /// 缺少纹理的 Draw 是否以品红棋盘格占位绘制, 默认仅 debug 构建开启
+    pub fallback_texture: bool,
+}
+impl Default for SpineAssetConfig {
+    fn default() -> Self {
+        Self {
+            vertex_buffer: (10 * 1024 * 1024, 60 * 1024),
+            uniform_buffer: 64 * 1024,
+            bone_buffer: 64 * 1024,
+            bind_group: (100 * 1024, 60 * 1024),
+            vertex_arena: 1024 * 1024,
+            index_arena: 256 * 1024,
+            texture_evict_frames: 0,
+            texture_retry: 2,
+            texture_retry_delay: 30,
+            fallback_texture: cfg!(debug_assertions),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PluginSpineRenderer;
+impl Plugin for PluginSpineRenderer {
+    fn build(&self, app: &mut App) {
+        if app.world.get_resource::<ShareAssetMgr<SamplerRes>>().is_none() {
+            app.insert_resource(ShareAssetMgr::<SamplerRes>::new(GarbageEmpty(), false, 32 * 1024, 30 * 1000));
+        }
+        if app.world.get_resource::<ShareAssetMgr<TextureRes>>().is_none() {
+            app.insert_resource(ShareAssetMgr::<TextureRes>::new(GarbageEmpty(), false, 32 * 1024 * 1024, 30 * 1000));
+        }
+
+        let cfg = if let Some(cfg) = app.world.get_resource::<SpineAssetConfig>() {
+            cfg.clone()
+        } else {
+            app.world.insert_resource(SpineAssetConfig::default());
+            app.world.get_resource::<SpineAssetConfig>().unwrap().clone()
+        };
+        
+        let device = app.world.get_resource::<PiRenderDevice>().unwrap().0.clone();
+        let mut resource = SpineResource::new(&device, cfg.vertex_buffer.clone(), cfg.uniform_buffer, cfg.bone_buffer, cfg.bind_group.clone(), cfg.vertex_arena, cfg.index_arena);
+        if cfg.fallback_texture {
+            resource.set_fallback(ActionSpine::fallback_texture(
+                app.world.get_resource::<PiRenderDevice>().unwrap(),
+                app.world.get_resource::<PiRenderQueue>().unwrap(),
+                app.world.get_resource::<ShareAssetMgr<TextureRes>>().unwrap(),
+                app.world.get_resource::<ShareAssetMgr<SamplerRes>>().unwrap(),
+            ));
+        }
+        app.insert_resource(ActionListSpine::default())
+            .insert_resource(resource)
+            .insert_resource(SpineRenderContext::new())
+            .insert_resource(SpineTextureLoad::default())
+            .insert_resource(SpineRenderStats::default())
+            .insert_resource(SpineTextureStore::default());
+        app.add_event::<SpineRenderErrorEvent>();
+        app.add_event::<SpineTargetChangedEvent>();
+        app.add_event::<SpineTextureLoadFailEvent>();
+
+        app.add_systems(
+			Update,
+            (
+                sys_spine_cmds,
+                sys_spine_render_apply,
+                sys_spine_texture_load_fail,
+                sys_spine_texture_load
+            ).chain().in_set(SpineSystemSet).before(PiRenderSystemSet)
+        );
+
+        app.add_system(apply_deferred.in_set(SpineSystemSet));
+
+        // log::warn!("PluginSpineRenderer");
+    }
 }
\ No newline at end of file
@@ -1,411 +1,959 @@
-use std::{ops::Range, sync::Arc};
-
-use bevy_ecs::prelude::Resource;
-use pi_assets::{asset::{Handle, GarbageEmpty}, mgr::AssetMgr};
-use pi_hash::XHashMap;
-
-
-use pi_map::smallvecmap::SmallVecMap;
-use pi_render::{
-    renderer::{
-        draw_obj::{DrawObj, DrawBindGroups, DrawBindGroup},
-        sampler::SamplerRes,
-        pipeline::KeyRenderPipelineState,
-        vertices::{RenderVertices, EVerticesBufferUsage, RenderIndices},
-        draw_obj_list::DrawList, vertex_buffer::{VertexBufferAllocator, EVertexBufferRange}
-    },
-    rhi::{
-        asset::{TextureRes, RenderRes},
-        device::RenderDevice, RenderQueue, bind_group::BindGroup, PrimitiveState,
-        sampler::SamplerDesc, options::RenderOptions
-    }
-};
-use pi_share::Share;
-
-use crate::{shaders::{KeySpineShader, KeySpinePipeline, SingleSpinePipelinePool, SingleSpineBindGroupLayout}, binds::param::{BindBufferAllocator, SpineBindBufferUsage}, bind_groups::SpineBindGroup, vertex_buffer::{SpineVertexBufferAllocator, SpineIndicesBufferAllocator}};
-
-
-#[derive(Resource)]
-pub struct SpineResource {
-    pipelines: SingleSpinePipelinePool,
-    bind_group_layouts: SingleSpineBindGroupLayout,
-    vballocator: VertexBufferAllocator,
-    bindallocator: BindBufferAllocator,
-    asset_mgr_bindgroup: Share<AssetMgr<RenderRes<BindGroup>>>,
-    pub(crate) verticeallocator: SpineVertexBufferAllocator,
-    pub(crate) indicesallocator: SpineIndicesBufferAllocator,
-}
-impl SpineResource {
-    pub fn new(device: &RenderDevice, vbcache: (usize, usize), bindcache: (usize, usize), bindgroupcache: (usize, usize)) -> Self {
-        let vballocator = VertexBufferAllocator::new(vbcache.0, vbcache.1);
-        let verticeallocator = SpineVertexBufferAllocator::new(1024 * 1024);
-        let indicesallocator = SpineIndicesBufferAllocator::new(1024 * 1024);
-        Self {
-            pipelines: SingleSpinePipelinePool::new(device),
-            bind_group_layouts: SingleSpineBindGroupLayout::new(device),
-            vballocator,
-            bindallocator: BindBufferAllocator::new(bindcache.0, bindcache.1),
-            asset_mgr_bindgroup: AssetMgr::<RenderRes::<BindGroup>>::new(GarbageEmpty(), false, bindgroupcache.0, bindgroupcache.1),
-            verticeallocator,
-            indicesallocator
-        }
-    }
-}
-
-pub struct SpineDraw {
-    bind_key: usize,
-    vertices: Vec<f32>,
-    indices: Option<Vec<u16>>,
-    verticeslen: u32,
-    indiceslen: u32,
-    texture: Option<Handle<TextureRes>>,
-    sampler: Option<Handle<SamplerRes>>,
-    shader: KeySpineShader,
-    pipeline: KeySpinePipeline,
-}
-
-pub struct RendererAsync {
-    pub(crate) binds: Vec<SpineBindBufferUsage>,
-    pub(crate) bind_groups: Vec<SpineBindGroup>,
-    pub(crate) draws: Vec<SpineDraw>,
-    pub(crate) drawobjs: DrawList,
-    pub(crate) _vbs: XHashMap<usize, EVertexBufferRange>,
-    pub(crate) _ibs: XHashMap<usize, EVertexBufferRange>,
-    pub(crate) shader: Option<KeySpineShader>,
-    pub(crate) blend: wgpu::BlendState,
-    pub(crate) enableblend: bool,
-    pub(crate) textures: XHashMap<u64, Handle<TextureRes>>,
-    pub(crate) samplers: XHashMap<SamplerDesc, Handle<SamplerRes>>,
-    uniform_param: Vec<Vec<f32>>,
-    texture: Option<Handle<TextureRes>>,
-    sampler: Option<Handle<SamplerRes>>,
-    pub target_format: wgpu::TextureFormat,
-}
-impl RendererAsync {
-    pub fn new() -> Self {
-        Self {
-            binds: vec![],
-            bind_groups: vec![],
-            draws: vec![],
-            drawobjs: DrawList::default(),
-            shader: None,
-            blend: wgpu::BlendState {
-                color: wgpu::BlendComponent {
-                    src_factor: wgpu::BlendFactor::SrcAlpha,
-                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                    operation: wgpu::BlendOperation::Add,
-                },
-                alpha: wgpu::BlendComponent {
-                    src_factor: wgpu::BlendFactor::SrcAlpha,
-                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                    operation: wgpu::BlendOperation::Add,
-                },
-            },
-            enableblend: true,
-            uniform_param: vec![],
-            texture: None,
-            sampler: None,
-            textures: XHashMap::default(),
-            samplers: XHashMap::default(),
-            target_format: wgpu::TextureFormat::Bgra8Unorm,
-            _vbs: XHashMap::default(),
-            _ibs: XHashMap::default(),
-        }
-    }
-    pub fn drawlist(
-        &mut self,
-        device: &RenderDevice,
-        queue: &RenderQueue,
-        resource: &mut SpineResource,
-        _asset_samplers: &Share<AssetMgr<SamplerRes>>,
-        _asset_textures: &Share<AssetMgr<TextureRes>>,
-    ) -> &DrawList {
-        let mut binds = vec![];
-        self.uniform_param.drain(..).for_each(|uniform_param| {
-            let bind = resource.bindallocator.allocate(device, queue, bytemuck::cast_slice(&uniform_param));
-            if let Some(bind) = &bind {
-                binds.push(bind.clone());
-            }
-        });
-
-        let mut index = 0;
-        self.draws.drain(..).for_each(|draw| {
-            let vbdata = bytemuck::cast_slice(&draw.vertices);
-
-            // let mut vbbuffer = None;
-            // if let Some(vbold) = self.vbs.remove(&index) {
-            //     if let EVertexBufferRange::NotUpdatable(range, _, _) = vbold {
-            //         if let Some(range) = resource.verticeallocator.collect(vbdata, draw.shader.vertices_bytes_per_element(), &mut resource.vballocator, device, queue) {
-            //             vbbuffer = Some(range);
-            //         }
-            //     }
-            // }
-
-            let buffer = if let Some(range) = resource.verticeallocator.collect(vbdata, draw.shader.vertices_bytes_per_element(), &mut resource.vballocator, device, queue) {
-                EVerticesBufferUsage::EVBRange(Arc::new(EVertexBufferRange::NotUpdatable(range.0, range.1, range.2)))
-            } else {
-                return;
-            };
-
-            // self.vbs.insert(index, vbbuffer.clone());
-            let mut vb = SmallVecMap::default();
-            vb.insert(0, RenderVertices { slot: 0, buffer, buffer_range: None, size_per_value: draw.shader.vertices_bytes_per_element() as u64 });
-
-            let ib = if let Some(indices) = &draw.indices {
-                let ibdata = bytemuck::cast_slice(indices);
-
-                let buffer = if let Some(range) = resource.indicesallocator.collect(ibdata, 2, &mut resource.vballocator, device, queue) {
-                    EVerticesBufferUsage::EVBRange(Arc::new(EVertexBufferRange::NotUpdatable(range.0, range.1, range.2)))
-                } else {
-                    return;
-                };
-
-                // self.ibs.insert(index, ib.clone());
-
-                let temp = RenderIndices { buffer, buffer_range: None, format: wgpu::IndexFormat::Uint16 };
-                Some(temp)
-            } else {
-                None
-            };
-
-            let bind = if let Some(bind) = binds.get(draw.bind_key) {
-                bind
-            } else {
-                // log::warn!("drawlist Err: Bind");
-                return;
-            };
-            let (vb, bindgroup) = match &draw.shader {
-                KeySpineShader::Colored => {
-                    let bindgroup = SpineBindGroup::colored(bind.0.clone(), device, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts);
-                    (vb, bindgroup)
-                },
-                KeySpineShader::ColoredTextured => {
-                    match (draw.texture.clone(), draw.sampler.clone()) {
-                        (Some(texture), Some(sampler)) => {
-                            let bindgroup = SpineBindGroup::two_colored_textured(bind.0.clone(), device, texture, sampler, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts);
-                            (vb, bindgroup)
-                        },
-                        _ => {
-                            // log::warn!("drawlist Err: tex");
-                            return;
-                        },
-                    }
-                },
-                KeySpineShader::TwoColoredTextured => {
-                    match (draw.texture.clone(), draw.sampler.clone()) {
-                        (Some(texture), Some(sampler)) => {
-                            let bindgroup = SpineBindGroup::two_colored_textured(bind.0.clone(), device, texture, sampler, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts);
-                            (vb, bindgroup)
-                        },
-                        // _ => {
-                        //     log::warn!("drawlist Err: tex {:?}, {:?}", );
-                        //     return
-                        // },
-                        (None, None) => {
-                            // log::warn!("drawlist Err: tex [None, None]");
-                            return;
-                        },
-                        (None, Some(_)) => {
-                            // log::warn!("drawlist Err: tex [None, Sampler]");
-                            return;
-                        },
-                        (Some(_), None) => {
-                            // log::warn!("drawlist Err: tex [Tex, None]");
-                            return;
-                        },
-                    }
-                },
-            };
-            
-            // log::warn!("IB : ");
-
-            
-            // log::warn!("Pipeline : ");
-
-            let pipeline = resource.pipelines.get(device, &draw.pipeline);
-            let mut bindgroups = DrawBindGroups::default();
-            bindgroups.insert_group(0, DrawBindGroup::Independ(bindgroup.bindgroup.clone()));
-            self.bind_groups.push(bindgroup);
-
-            let draw = DrawObj {
-                pipeline,
-                bindgroups,
-                vertices: vb,
-                vertex: Range { start: 0, end: draw.verticeslen },
-                instances: 0..1,
-                indices: ib,
-            };
-
-            index += 1;
-            self.drawobjs.list.push(Arc::new(draw));
-            // log::warn!("drawlist : {:?}", self.drawobjs.list.len());
-        });
-
-        &self.drawobjs
-    }
-    pub fn reset(&mut self) {
-        self.bind_groups.clear();
-        self.binds.clear();
-        self.draws.clear();
-        self.drawobjs.list.clear();
-    }
-    pub fn viewport(&mut self, _viewport: &[f32]) {
-        //
-    }
-    
-    pub fn shader(
-        &mut self,
-        shader: Option<KeySpineShader>,
-    ) {
-        self.shader = shader;
-    }
-
-    pub fn uniform(
-        &mut self,
-        uniform_param: Vec<f32>,
-    ) {
-        self.uniform_param.push(uniform_param);
-    }
-    pub fn blend(&mut self, flag: bool) {
-        self.enableblend = flag;
-    }
-    pub fn blend_mode(
-        &mut self,
-        src_factor: wgpu::BlendFactor,
-        dst_factor: wgpu::BlendFactor,
-    ) {
-        self.blend = wgpu::BlendState {
-            color: wgpu::BlendComponent {
-                src_factor,
-                dst_factor,
-                operation: wgpu::BlendOperation::Add,
-            },
-            alpha: wgpu::BlendComponent {
-                src_factor,
-                dst_factor,
-                operation: wgpu::BlendOperation::Add,
-            },
-        }
-    }
-
-    pub fn texture(
-        &mut self,
-        texture: Option<Handle<TextureRes>>,
-        sampler: Option<Handle<SamplerRes>>,
-    ) {
-        // log::warn!("texture {:?}", texture);
-        self.texture = texture;
-        self.sampler = sampler;
-    }
-
-    pub fn record_texture(
-        &mut self,
-        key_texture: u64,
-        texture: Handle<TextureRes>,
-    ) {
-        // log::warn!("record_texture {:?}", key_texture);
-        self.textures.insert(key_texture, texture);
-    }
-
-    pub fn record_sampler(
-        &mut self,
-        key_sampler: SamplerDesc,
-        sampler: Handle<SamplerRes>,
-    ) {
-        // log::warn!("record_sampler {:?}", key_sampler);
-        self.samplers.insert(key_sampler, sampler);
-    }
-
-    pub fn remove_texture(
-        &mut self,
-        key_texture: u64,
-    ) {
-        self.textures.remove(&key_texture);
-    }
-
-    pub fn draw(
-        &mut self,
-        vertices: Vec<f32>,
-        indices: Option<Vec<u16>>,
-        vertices_len: u32,
-        indices_len: u32,
-        _renderopt: &RenderOptions,
-    ) {
-        let shader = if let Some(shader) = &self.shader {
-            shader
-        } else {
-            // log::warn!("draw Err: shader");
-            return;
-        };
-
-        let indices = if let Some(indices) = indices {
-            Some(indices)
-        } else {
-            None
-        };
-
-        if self.uniform_param.len() == 0 {
-            // log::warn!("draw Err: uniform_param");
-            return;
-        }
-
-        match shader {
-            KeySpineShader::Colored => {
-            },
-            KeySpineShader::ColoredTextured => {
-                if self.texture.is_none() && self.sampler.is_none() {
-                    return;
-                }
-            },
-            KeySpineShader::TwoColoredTextured => {
-                if self.texture.is_none() && self.sampler.is_none() {
-                    return;
-                }
-            },
-        };
-
-        let blend = if self.enableblend {
-            Some(self.blend.clone())
-        } else {
-            None
-        };
-
-        // let unclipped_depth = renderopt.features & wgpu::Features::DEPTH_CLIP_CONTROL == wgpu::Features::DEPTH_CLIP_CONTROL;
-
-        let key: KeySpinePipeline = KeySpinePipeline {
-            key_shader: shader.clone(),
-            key_state: KeyRenderPipelineState {
-                primitive: PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    front_face: wgpu::FrontFace::Ccw,
-                    
-                    // #[cfg(not(target_arch = "wasm32"))]
-                    // unclipped_depth: true,
-
-                    cull_mode: None,
-                    ..Default::default()
-                },
-                multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
-                depth_stencil: None,
-                target_state: Some(wgpu::ColorTargetState { format: self.target_format, blend, write_mask: wgpu::ColorWrites::ALL }),
-            },
-        };
-        
-        let draw = SpineDraw {
-            bind_key: self.uniform_param.len() - 1,
-            vertices,
-            indices,
-            verticeslen: vertices_len,
-            indiceslen: indices_len,
-            texture: self.texture.clone(),
-            sampler: self.sampler.clone(),
-            shader: shader.clone(),
-            pipeline: key,
-        };
-
-        self.draws.push(draw);
-        // log::warn!("Draws: {:?}", self.draws.len());
-    }
-}
+use std::sync::Arc;
+
+use bevy_ecs::prelude::Resource;
+use pi_assets::{asset::{Handle, GarbageEmpty, Size}, mgr::AssetMgr};
+use pi_hash::{XHashMap, XHashSet};
+
+
+use pi_render::{
+    renderer::{
+        sampler::SamplerRes,
+        pipeline::KeyRenderPipelineState,
+        vertex_buffer::{VertexBufferAllocator, EVertexBufferRange, NotUpdatableBufferRange}
+    },
+    rhi::{
+        asset::{TextureRes, RenderRes},
+        device::RenderDevice, RenderQueue, bind_group::BindGroup, PrimitiveState,
+        sampler::SamplerDesc, options::RenderOptions, pipeline::RenderPipeline
+    }
+};
+use pi_share::Share;
+
+use crate::{error::SpineRenderError, stats::SpineFrameStats, blit::SpineBlit, shaders::{KeySpineShader, KeySpinePipeline, ESpineVertexPacking, SingleSpinePipelinePool, SingleSpineBindGroupLayout}, binds::{param::{SpineUniformRing, BindParam, BindBones}, uniform::SpineUniform}, bind_groups::{SpineBindGroup, UsedBindGroupSet0, UsedBindGroupSet1}, vertex_buffer::{SpineVertexBufferAllocator, SpineIndicesBufferAllocator, SpineMesh}};
+
+
+#[derive(Resource)]
+pub struct SpineResource {
+    pipelines: SingleSpinePipelinePool,
+    bind_group_layouts: SingleSpineBindGroupLayout,
+    vballocator: VertexBufferAllocator,
+    pub(crate) uniforms: SpineUniformRing,
+    /// 蒙皮骨骼矩阵的环形 Buffer
+    pub(crate) bones: SpineUniformRing,
+    asset_mgr_bindgroup: Share<AssetMgr<RenderRes<BindGroup>>>,
+    pub(crate) verticeallocator: SpineVertexBufferAllocator,
+    pub(crate) indicesallocator: SpineIndicesBufferAllocator,
+    pub(crate) blit: SpineBlit,
+    /// 缺少纹理的 Draw 使用的占位纹理与采样器
+    fallback: Option<(Handle<TextureRes>, Handle<SamplerRes>)>,
+}
+impl SpineResource {
+    pub fn new(device: &RenderDevice, vbcache: (usize, usize), uniform_buffer: u32, bone_buffer: u32, bindgroupcache: (usize, usize), vertex_arena: u32, index_arena: u32) -> Self {
+        let vballocator = VertexBufferAllocator::new(vbcache.0, vbcache.1);
+        let verticeallocator = SpineVertexBufferAllocator::new(vertex_arena);
+        let indicesallocator = SpineIndicesBufferAllocator::new(index_arena);
+        Self {
+            pipelines: SingleSpinePipelinePool::new(device),
+            bind_group_layouts: SingleSpineBindGroupLayout::new(device),
+            vballocator,
+            uniforms: SpineUniformRing::new(BindParam::SIZE as u32, uniform_buffer),
+            bones: SpineUniformRing::new(BindBones::SIZE as u32, bone_buffer),
+            asset_mgr_bindgroup: AssetMgr::<RenderRes::<BindGroup>>::new(GarbageEmpty(), false, bindgroupcache.0, bindgroupcache.1),
+            verticeallocator,
+            indicesallocator,
+            blit: SpineBlit::new(device),
+            fallback: None,
+        }
+    }
+    pub fn set_fallback(&mut self, fallback: Option<(Handle<TextureRes>, Handle<SamplerRes>)>) {
+        self.fallback = fallback;
+    }
+    pub fn fallback(&self) -> Option<&(Handle<TextureRes>, Handle<SamplerRes>)> {
+        self.fallback.as_ref()
+    }
+    /// BindGroup 缓存当前占用
+    pub fn bind_group_cache_size(&self) -> usize {
+        self.asset_mgr_bindgroup.size()
+    }
+    pub fn pipeline_cache_size(&self) -> usize {
+        self.pipelines.cache_size()
+    }
+}
+
+pub struct SpineDraw {
+    /// 自上次 Reset 起的 Draw 命令序号, 保留的绘制沿用记录时的序号
+    index: usize,
+    bind_key: usize,
+    /// GPU 蒙皮的常驻网格, 或内容未变的保留绘制转存的常驻 Buffer
+    mesh: Option<Arc<SpineMesh>>,
+    /// 骨骼数据序号
+    bones_key: usize,
+    /// 记录时按管线的顶点压缩方式打包的顶点字节
+    vertices: Vec<u8>,
+    indices: Option<Vec<u16>>,
+    vertex_count: u32,
+    indiceslen: u32,
+    texture: Option<Handle<TextureRes>>,
+    sampler: Option<Handle<SamplerRes>>,
+    shader: KeySpineShader,
+    pipeline: KeySpinePipeline,
+    /// 纹理为纹理数组 (D2Array 视图)
+    array: bool,
+}
+impl SpineDraw {
+    /// 转存为常驻 Buffer, 无索引时按顶点顺序生成索引
+    fn persist(&self, device: &RenderDevice) -> Option<SpineMesh> {
+        let indices = match &self.indices {
+            Some(indices) => indices.clone(),
+            None if self.vertex_count <= u16::MAX as u32 + 1 => (0..self.vertex_count).map(|i| i as u16).collect(),
+            None => return None,
+        };
+        if self.vertex_count == 0 || indices.is_empty() {
+            return None;
+        }
+        Some(SpineMesh::with_bytes(device, &self.vertices, &indices, self.vertex_count))
+    }
+}
+
+/// 一次绘制所需的 GPU 资源, Uniform 以动态偏移指定
+pub struct SpineDrawObj {
+    pub(crate) pipeline: Handle<RenderRes<RenderPipeline>>,
+    pub(crate) bindgroup: UsedBindGroupSet0,
+    /// GPU 蒙皮的骨骼矩阵
+    pub(crate) bones: Option<UsedBindGroupSet1>,
+    pub(crate) vertices: Option<(Arc<NotUpdatableBufferRange>, u32, u32)>,
+    pub(crate) indices: Option<(Arc<NotUpdatableBufferRange>, u32, u32)>,
+    /// 常驻网格, 存在时 vertices/indices 为 None
+    pub(crate) mesh: Option<Arc<SpineMesh>>,
+    pub(crate) vertex_count: u32,
+}
+impl SpineDrawObj {
+    pub fn draw<'a>(&'a self, renderpass: &mut wgpu::RenderPass<'a>) {
+        renderpass.set_pipeline(&self.pipeline);
+        renderpass.set_bind_group(0, &self.bindgroup.bindgroup, &self.bindgroup.offsets);
+        if let Some(bones) = &self.bones {
+            renderpass.set_bind_group(1, &bones.bindgroup, &bones.offsets);
+        }
+        if let Some(mesh) = &self.mesh {
+            renderpass.set_vertex_buffer(0, mesh.vertices.slice(..));
+            renderpass.set_index_buffer(mesh.indices.slice(..), wgpu::IndexFormat::Uint16);
+            renderpass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            return;
+        }
+        if let Some((buffer, start, end)) = &self.vertices {
+            renderpass.set_vertex_buffer(0, buffer.buffer().slice(*start as u64..*end as u64));
+        }
+        if let Some((buffer, start, end)) = &self.indices {
+            renderpass.set_index_buffer(buffer.buffer().slice(*start as u64..*end as u64), wgpu::IndexFormat::Uint16);
+            renderpass.draw_indexed(0..((end - start) / 2), 0, 0..1);
+        } else {
+            renderpass.draw(0..self.vertex_count, 0..1);
+        }
+    }
+}
+
+pub struct RendererAsync {
+    pub(crate) bind_groups: Vec<SpineBindGroup>,
+    pub(crate) draws: Vec<SpineDraw>,
+    pub(crate) drawobjs: Vec<SpineDrawObj>,
+    pub(crate) _vbs: XHashMap<usize, EVertexBufferRange>,
+    pub(crate) _ibs: XHashMap<usize, EVertexBufferRange>,
+    pub(crate) shader: Option<KeySpineShader>,
+    pub(crate) blend: wgpu::BlendState,
+    pub(crate) enableblend: bool,
+    pub(crate) textures: XHashMap<u64, Handle<TextureRes>>,
+    pub(crate) samplers: XHashMap<SamplerDesc, Handle<SamplerRes>>,
+    /// 记录为纹理数组 (D2Array 视图) 的纹理
+    texture_arrays: XHashSet<u64>,
+    /// 记录的纹理与采样器最近一次被使用的帧
+    texture_frames: XHashMap<u64, u64>,
+    sampler_frames: XHashMap<SamplerDesc, u64>,
+    frame: u64,
+    uniform_param: Vec<SpineUniform>,
+    /// 本帧各 Uniform 在环形 Buffer 中的偏移
+    uniform_offsets: Vec<wgpu::DynamicOffset>,
+    /// GPU 蒙皮的骨骼矩阵, 与 Uniform 一样保留到 Reset
+    bones_param: Vec<Vec<f32>>,
+    bones_offsets: Vec<wgpu::DynamicOffset>,
+    /// GPU 蒙皮的常驻网格, Reset 时不清除
+    meshes: XHashMap<u64, Arc<SpineMesh>>,
+    texture: Option<Handle<TextureRes>>,
+    sampler: Option<Handle<SamplerRes>>,
+    pub target_format: wgpu::TextureFormat,
+    /// 多重采样数, 仅 Atlas 目标支持大于 1
+    pub sample_count: u32,
+    /// 后续绘制的顶点压缩布局
+    vertex_packing: ESpineVertexPacking,
+    errors: Vec<SpineRenderError>,
+    draw_count: usize,
+    /// 清屏颜色, None 则保留已有内容
+    clear: Option<wgpu::Color>,
+    clear_offset: Option<wgpu::DynamicOffset>,
+    clear_bind_group: Option<SpineBindGroup>,
+    clearobj: Option<SpineDrawObj>,
+    viewport: Option<[f32; 4]>,
+    scissor: Option<[u32; 4]>,
+    /// 绘制或输出参数自上次重建后有变化
+    dirty: bool,
+    /// 本帧是否重建并绘制
+    active: bool,
+    stats: SpineFrameStats,
+}
+impl RendererAsync {
+    pub fn new() -> Self {
+        Self {
+            bind_groups: vec![],
+            draws: vec![],
+            drawobjs: vec![],
+            shader: None,
+            blend: wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+            enableblend: true,
+            uniform_param: vec![],
+            uniform_offsets: vec![],
+            bones_param: vec![],
+            bones_offsets: vec![],
+            meshes: XHashMap::default(),
+            texture: None,
+            sampler: None,
+            textures: XHashMap::default(),
+            samplers: XHashMap::default(),
+            texture_arrays: XHashSet::default(),
+            texture_frames: XHashMap::default(),
+            sampler_frames: XHashMap::default(),
+            frame: 0,
+            target_format: wgpu::TextureFormat::Bgra8Unorm,
+            sample_count: 1,
+            vertex_packing: ESpineVertexPacking::Float32,
+            _vbs: XHashMap::default(),
+            _ibs: XHashMap::default(),
+            errors: vec![],
+            draw_count: 0,
+            clear: None,
+            clear_offset: None,
+            clear_bind_group: None,
+            clearobj: None,
+            viewport: None,
+            scissor: None,
+            dirty: true,
+            active: true,
+            stats: SpineFrameStats::default(),
+        }
+    }
+    /// 本帧被跳过的绘制
+    pub fn errors(&self) -> &[SpineRenderError] {
+        &self.errors
+    }
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+    /// 本帧是否绘制; 为 false 时目标保留上次的内容
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+    /// 每帧 uniforms 之前调用; retained 表示目标内容可跨帧保留 (Atlas 目标)
+    pub fn prepare(&mut self, retained: bool) {
+        self.active = self.dirty || !retained;
+        self.stats = SpineFrameStats::default();
+    }
+    /// 推进帧计数, 并释放超过 frames 帧未被使用的记录纹理与采样器; frames 为 0 时不释放.
+    /// 保留的 Draw 与当前绑定引用的纹理视为本帧使用
+    pub fn evict(&mut self, frames: u32) {
+        self.frame += 1;
+        let frame = self.frame;
+
+        let texture_frames = &mut self.texture_frames;
+        let sampler_frames = &mut self.sampler_frames;
+        let mut used = |texture: &Option<Handle<TextureRes>>, sampler: &Option<Handle<SamplerRes>>| {
+            if let Some(texture) = texture {
+                if let Some(last) = texture_frames.get_mut(texture.key()) { *last = frame; }
+            }
+            if let Some(sampler) = sampler {
+                if let Some(last) = sampler_frames.get_mut(sampler.key()) { *last = frame; }
+            }
+        };
+        self.draws.iter().for_each(|draw| used(&draw.texture, &draw.sampler));
+        used(&self.texture, &self.sampler);
+
+        if frames == 0 {
+            return;
+        }
+        let frames = frames as u64;
+        self.texture_frames.retain(|_, last| frame - *last <= frames);
+        self.sampler_frames.retain(|_, last| frame - *last <= frames);
+        let (texture_frames, sampler_frames) = (&self.texture_frames, &self.sampler_frames);
+        self.textures.retain(|key, _| texture_frames.contains_key(key));
+        self.texture_arrays.retain(|key| texture_frames.contains_key(key));
+        self.samplers.retain(|key, _| sampler_frames.contains_key(key));
+    }
+    /// 记录的纹理及其字节数
+    pub fn texture_usage(&self) -> Vec<(u64, usize)> {
+        self.textures.iter().map(|(key, texture)| (*key, texture.size())).collect()
+    }
+    /// 本帧的绘制统计
+    pub fn stats(&self) -> &SpineFrameStats {
+        &self.stats
+    }
+    /// Draw 使用的纹理与采样器; 未设置纹理时使用 SpineResource 的占位纹理.
+    /// 纹理须与 Shader 一致: 仅 TwoColoredTexturedArray 使用纹理数组
+    fn draw_texture(draw: &SpineDraw, resource: &SpineResource) -> Result<(Handle<TextureRes>, Handle<SamplerRes>), SpineRenderError> {
+        if draw.texture.is_some() && draw.array != (draw.shader == KeySpineShader::TwoColoredTexturedArray) {
+            return Err(SpineRenderError::MissingTexture { draw: draw.index, shader: draw.shader });
+        }
+        match (draw.texture.clone(), draw.sampler.clone(), &resource.fallback) {
+            (Some(texture), Some(sampler), _) => Ok((texture, sampler)),
+            (None, _, Some((texture, sampler))) => Ok((texture.clone(), sampler.clone())),
+            (None, _, None) => Err(SpineRenderError::MissingTexture { draw: draw.index, shader: draw.shader }),
+            (Some(_), None, _) => Err(SpineRenderError::MissingSampler { draw: draw.index, shader: draw.shader }),
+        }
+    }
+    /// 保留的绘制或记录的纹理中是否有 key 对应的纹理
+    pub fn uses_texture(&self, key: u64) -> bool {
+        self.textures.contains_key(&key)
+            || self.draws.iter().any(|draw| draw.texture.as_ref().map(|texture| *texture.key() == key).unwrap_or(false))
+    }
+    pub(crate) fn push_error(&mut self, error: SpineRenderError) {
+        self.errors.push(error);
+    }
+    /// 每帧处理命令前调用
+    pub fn clear_errors(&mut self) {
+        self.errors.clear();
+    }
+    /// 将本帧 Uniform 及骨骼矩阵写入环形 Buffer; 须在所有渲染器的 drawlist 之前调用
+    pub fn uniforms(
+        &mut self,
+        ring: &mut SpineUniformRing,
+        bones: &mut SpineUniformRing,
+    ) {
+        self.uniform_offsets.clear();
+        self.bones_offsets.clear();
+        if !self.active {
+            self.clear_offset = None;
+            return;
+        }
+        self.uniform_param.iter().for_each(|uniform_param| {
+            self.uniform_offsets.push(ring.push(uniform_param.as_bytes()));
+        });
+        self.clear_offset = if self.need_clear_draw() {
+            Some(ring.push(SpineUniform::default().as_bytes()))
+        } else {
+            None
+        };
+        let count = self.uniform_offsets.len() + self.clear_offset.iter().count();
+        self.stats.upload_bytes += (count * SpineUniform::FLOATS * 4) as u64;
+        self.bones_param.iter().for_each(|bones_param| {
+            self.bones_offsets.push(bones.push(bytemuck::cast_slice(bones_param)));
+        });
+        self.stats.upload_bytes += (self.bones_offsets.len() * BindBones::SIZE) as u64;
+    }
+    pub fn drawlist(
+        &mut self,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        resource: &mut SpineResource,
+        _asset_samplers: &Share<AssetMgr<SamplerRes>>,
+        _asset_textures: &Share<AssetMgr<TextureRes>>,
+    ) -> &Vec<SpineDrawObj> {
+        self.bind_groups.clear();
+        self.drawobjs.clear();
+        self.clearobj = None;
+        self.clear_bind_group = None;
+        if !self.active {
+            return &self.drawobjs;
+        }
+        // 未变化时保留的绘制与上一帧相同, 可转存为常驻 Buffer 而不再逐帧上传
+        let unchanged = !self.dirty;
+        self.dirty = false;
+
+        if let (Some(draw), Some(offset)) = (self.clear_draw(), self.clear_offset.take()) {
+            match Self::drawobj(&draw, Some(offset), None, device, queue, resource, &mut self.stats) {
+                Ok((draw, bindgroup)) => {
+                    self.clearobj = Some(draw);
+                    self.clear_bind_group = Some(bindgroup);
+                },
+                Err(_) => self.errors.push(SpineRenderError::Clear),
+            }
+        }
+
+        let errors = &mut self.errors;
+        let uniform_offsets = &self.uniform_offsets;
+        let bones_offsets = &self.bones_offsets;
+        let stats = &mut self.stats;
+        let (target_format, sample_count) = (self.target_format, self.sample_count);
+        self.draws.iter_mut().for_each(|draw| {
+            if unchanged && draw.mesh.is_none() {
+                draw.mesh = draw.persist(device).map(Arc::new);
+            }
+            // 保留的绘制在目标格式或多重采样数变化后须与新的目标一致
+            draw.pipeline.key_state.multisample.count = sample_count;
+            if let Some(target_state) = &mut draw.pipeline.key_state.target_state {
+                target_state.format = target_format;
+            }
+            let bones_offset = if draw.shader == KeySpineShader::Skinned { bones_offsets.get(draw.bones_key).cloned() } else { None };
+            match Self::drawobj(draw, uniform_offsets.get(draw.bind_key).cloned(), bones_offset, device, queue, resource, stats) {
+                Ok((obj, bindgroup)) => {
+                    self.bind_groups.push(bindgroup);
+                    self.drawobjs.push(obj);
+                },
+                Err(e) => errors.push(e),
+            }
+            // log::warn!("drawlist : {:?}", self.drawobjs.list.len());
+        });
+
+        &self.drawobjs
+    }
+    fn drawobj(
+        draw: &SpineDraw,
+        offset: Option<wgpu::DynamicOffset>,
+        bones_offset: Option<wgpu::DynamicOffset>,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        resource: &mut SpineResource,
+        stats: &mut SpineFrameStats,
+    ) -> Result<(SpineDrawObj, SpineBindGroup), SpineRenderError> {
+        let index = draw.index;
+        let vbdata = draw.vertices.as_slice();
+
+        // let mut vbbuffer = None;
+        // if let Some(vbold) = self.vbs.remove(&index) {
+        //     if let EVertexBufferRange::NotUpdatable(range, _, _) = vbold {
+        //         if let Some(range) = resource.verticeallocator.collect(vbdata, draw.shader.vertices_bytes_per_element(), &mut resource.vballocator, device, queue) {
+        //             vbbuffer = Some(range);
+        //         }
+        //     }
+        // }
+
+        // 常驻网格无需上传
+        let (vertices, indices) = if draw.mesh.is_some() {
+            (None, None)
+        } else {
+            let vertices = if let Some(range) = resource.verticeallocator.collect(vbdata, &mut resource.vballocator, device, queue) {
+                range
+            } else {
+                return Err(SpineRenderError::VertexAllocation { draw: index, bytes: vbdata.len() });
+            };
+
+            // self.vbs.insert(index, vbbuffer.clone());
+
+            let indices = if let Some(indices) = &draw.indices {
+                let ibdata = bytemuck::cast_slice(indices);
+
+                if let Some(range) = resource.indicesallocator.collect(ibdata, &mut resource.vballocator, device, queue) {
+                    Some(range)
+                } else {
+                    return Err(SpineRenderError::IndexAllocation { draw: index, bytes: ibdata.len() });
+                }
+
+                // self.ibs.insert(index, ib.clone());
+            } else {
+                None
+            };
+            (Some(vertices), indices)
+        };
+
+        let offset = if let Some(offset) = offset {
+            offset
+        } else {
+            return Err(SpineRenderError::MissingUniform { draw: index });
+        };
+        let bindgroup = match &draw.shader {
+            KeySpineShader::Colored => {
+                SpineBindGroup::colored(&resource.uniforms, offset, device, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts)
+            },
+            KeySpineShader::ColoredTextured => {
+                let (texture, sampler) = Self::draw_texture(draw, resource)?;
+                SpineBindGroup::colored_textured(&resource.uniforms, offset, device, texture, sampler, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts)
+            },
+            KeySpineShader::TwoColoredTextured => {
+                let (texture, sampler) = Self::draw_texture(draw, resource)?;
+                SpineBindGroup::two_colored_textured(&resource.uniforms, offset, device, texture, sampler, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts)
+            },
+            KeySpineShader::TwoColoredTexturedArray => {
+                // 占位纹理不是纹理数组
+                if draw.texture.is_none() {
+                    return Err(SpineRenderError::MissingTexture { draw: index, shader: draw.shader });
+                }
+                let (texture, sampler) = Self::draw_texture(draw, resource)?;
+                SpineBindGroup::two_colored_textured_array(&resource.uniforms, offset, device, texture, sampler, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts)
+            },
+            KeySpineShader::Skinned => {
+                if draw.mesh.is_none() {
+                    return Err(SpineRenderError::MissingMesh { draw: index });
+                }
+                let (texture, sampler) = Self::draw_texture(draw, resource)?;
+                SpineBindGroup::colored_textured(&resource.uniforms, offset, device, texture, sampler, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts)
+            },
+        };
+        let bindgroup = if let Some(bindgroup) = bindgroup {
+            bindgroup
+        } else {
+            return Err(SpineRenderError::BindGroup { draw: index, shader: draw.shader });
+        };
+
+        let bones = if draw.shader == KeySpineShader::Skinned {
+            let bones_offset = if let Some(bones_offset) = bones_offset {
+                bones_offset
+            } else {
+                return Err(SpineRenderError::MissingBones { draw: index });
+            };
+            match SpineBindGroup::bones(&resource.bones, bones_offset, device, &resource.asset_mgr_bindgroup, &resource.bind_group_layouts) {
+                Some((bones, created)) => {
+                    if created {
+                        stats.bind_groups_created += 1;
+                    } else {
+                        stats.bind_group_hits += 1;
+                    }
+                    Some(bones)
+                },
+                None => return Err(SpineRenderError::BindGroup { draw: index, shader: draw.shader }),
+            }
+        } else {
+            None
+        };
+
+        let created = resource.pipelines.created();
+        let pipeline = resource.pipelines.get(device, &draw.pipeline);
+        stats.pipelines_created += (resource.pipelines.created() - created) as u32;
+        let pipeline = if let Some(pipeline) = pipeline {
+            pipeline
+        } else {
+            return Err(SpineRenderError::Pipeline { draw: index, shader: draw.shader });
+        };
+
+        let obj = SpineDrawObj {
+            pipeline,
+            bindgroup: bindgroup.bindgroup.clone(),
+            bones,
+            vertices,
+            indices,
+            mesh: draw.mesh.clone(),
+            vertex_count: match &draw.mesh {
+                Some(mesh) => mesh.vertex_count,
+                None => draw.vertex_count,
+            },
+        };
+
+        stats.draw_calls += 1;
+        stats.vertices += obj.vertex_count as u64;
+        if let Some(mesh) = &obj.mesh {
+            stats.indices += mesh.index_count as u64;
+        }
+        if let Some((_, start, end)) = &obj.vertices {
+            stats.upload_bytes += (end - start) as u64;
+        }
+        if let Some((_, start, end)) = &obj.indices {
+            stats.indices += ((end - start) / 2) as u64;
+            stats.upload_bytes += (end - start) as u64;
+        }
+        if bindgroup.created {
+            stats.bind_groups_created += 1;
+        } else {
+            stats.bind_group_hits += 1;
+        }
+
+        Ok((obj, bindgroup))
+    }
+    /// 清屏以覆盖视口的 Colored 矩形实现, 无混合
+    fn clear_draw(&self) -> Option<SpineDraw> {
+        let color = self.clear?;
+        let (r, g, b, a) = (color.r as f32, color.g as f32, color.b as f32, color.a as f32);
+        let mut vertices = Vec::with_capacity(6 * 6);
+        [(-1., -1.), (1., -1.), (1., 1.), (-1., -1.), (1., 1.), (-1., 1.)].iter().for_each(|(x, y)| {
+            vertices.extend_from_slice(&[*x, *y, r, g, b, a]);
+        });
+        let pipeline = self.pipeline_key(KeySpineShader::Colored, None);
+        Some(SpineDraw {
+            index: usize::MAX,
+            bind_key: 0,
+            mesh: None,
+            bones_key: 0,
+            vertices: pipeline.packing.pack(KeySpineShader::Colored, &vertices),
+            indices: None,
+            vertex_count: 6,
+            indiceslen: 0,
+            texture: None,
+            sampler: None,
+            shader: KeySpineShader::Colored,
+            pipeline,
+            array: false,
+        })
+    }
+    /// 多重采样时由 Pass 的 LoadOp 清屏, 无需绘制
+    fn need_clear_draw(&self) -> bool {
+        self.clear.is_some() && self.sample_count <= 1
+    }
+    /// None 表示保留目标已有内容
+    pub fn clear_color(&self) -> Option<wgpu::Color> {
+        self.clear
+    }
+    pub fn vertex_packing(&self) -> ESpineVertexPacking {
+        self.vertex_packing
+    }
+    pub fn set_vertex_packing(&mut self, packing: ESpineVertexPacking) {
+        self.dirty = true;
+        self.vertex_packing = packing;
+    }
+    pub fn set_clear_color(&mut self, clear: Option<wgpu::Color>) {
+        self.dirty = true;
+        self.clear = clear;
+    }
+    /// 在目标区域 rect (x, y, w, h) 内绘制: 清屏覆盖整个区域, 其余绘制使用视口与裁剪;
+    /// 视口保持设置的完整尺寸, 仅截到附件尺寸 bounds (w, h) 以内, 超出目标区域的部分由裁剪去除
+    pub fn render_rect<'a>(&'a self, renderpass: &mut wgpu::RenderPass<'a>, rect: (u32, u32, u32, u32), bounds: (u32, u32)) {
+        let (x, y, w, h) = rect;
+        if w == 0 || h == 0 {
+            return;
+        }
+        renderpass.set_viewport(x as f32, y as f32, w as f32, h as f32, 0., 1.);
+        renderpass.set_scissor_rect(x, y, w, h);
+        if let Some(clearobj) = &self.clearobj {
+            clearobj.draw(renderpass);
+        }
+
+        let (x0, y0, x1, y1) = (x as f32, y as f32, (x + w) as f32, (y + h) as f32);
+        let (vx0, vy0, vx1, vy1) = match self.viewport {
+            Some([vx, vy, vw, vh]) => (
+                (x0 + vx).max(0.), (y0 + vy).max(0.),
+                (x0 + vx + vw).min(bounds.0 as f32), (y0 + vy + vh).min(bounds.1 as f32),
+            ),
+            None => (x0, y0, x1, y1),
+        };
+        if vx1 <= vx0 || vy1 <= vy0 {
+            return;
+        }
+
+        let mut sx0 = (vx0.floor() as u32).max(x);
+        let mut sy0 = (vy0.floor() as u32).max(y);
+        let mut sx1 = (vx1.ceil() as u32).min(x + w);
+        let mut sy1 = (vy1.ceil() as u32).min(y + h);
+        if let Some([sx, sy, sw, sh]) = self.scissor {
+            sx0 = sx0.max(x + sx);
+            sy0 = sy0.max(y + sy);
+            sx1 = sx1.min((x + sx).saturating_add(sw));
+            sy1 = sy1.min((y + sy).saturating_add(sh));
+        }
+        if sx1 <= sx0 || sy1 <= sy0 {
+            return;
+        }
+
+        renderpass.set_viewport(vx0, vy0, vx1 - vx0, vy1 - vy0, 0., 1.);
+        renderpass.set_scissor_rect(sx0, sy0, sx1 - sx0, sy1 - sy0);
+        self.drawobjs.iter().for_each(|draw| {
+            draw.draw(renderpass);
+        });
+    }
+    pub fn render<'a>(&'a self, renderpass: &mut wgpu::RenderPass<'a>) {
+        if let Some(clearobj) = &self.clearobj {
+            clearobj.draw(renderpass);
+        }
+        self.drawobjs.iter().for_each(|draw| {
+            draw.draw(renderpass);
+        });
+    }
+    /// 清空保留的绘制与 Uniform
+    pub fn reset(&mut self) {
+        self.dirty = true;
+        self.draw_count = 0;
+        self.bind_groups.clear();
+        self.draws.clear();
+        self.uniform_param.clear();
+        self.bones_param.clear();
+        self.drawobjs.clear();
+        self.clearobj = None;
+        self.clear_bind_group = None;
+    }
+    /// 相对目标区域的 x, y, w, h; 少于 4 个值时恢复为整个目标区域
+    pub fn viewport(&mut self, viewport: &[f32]) {
+        self.dirty = true;
+        self.viewport = if viewport.len() >= 4 {
+            Some([viewport[0], viewport[1], viewport[2], viewport[3]])
+        } else {
+            None
+        };
+    }
+    /// 相对目标区域的 x, y, w, h
+    pub fn scissor(&mut self, scissor: Option<[u32; 4]>) {
+        self.dirty = true;
+        self.scissor = scissor;
+    }
+    
+    pub fn shader(
+        &mut self,
+        shader: Option<KeySpineShader>,
+    ) {
+        self.shader = shader;
+    }
+
+    pub fn uniform(
+        &mut self,
+        uniform_param: SpineUniform,
+    ) {
+        self.dirty = true;
+        self.uniform_param.push(uniform_param);
+    }
+    pub fn blend(&mut self, flag: bool) {
+        self.enableblend = flag;
+    }
+    pub fn blend_mode(
+        &mut self,
+        src_factor: wgpu::BlendFactor,
+        dst_factor: wgpu::BlendFactor,
+    ) {
+        self.blend = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor,
+                dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent {
+                src_factor,
+                dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            },
+        }
+    }
+
+    pub fn texture(
+        &mut self,
+        texture: Option<Handle<TextureRes>>,
+        sampler: Option<Handle<SamplerRes>>,
+    ) {
+        // log::warn!("texture {:?}", texture);
+        self.texture = texture;
+        self.sampler = sampler;
+    }
+
+    pub fn record_texture(
+        &mut self,
+        key_texture: u64,
+        texture: Handle<TextureRes>,
+    ) {
+        // log::warn!("record_texture {:?}", key_texture);
+        self.texture_frames.insert(key_texture, self.frame);
+        self.textures.insert(key_texture, texture);
+    }
+
+    /// 标记记录的纹理是否为纹理数组
+    pub fn record_texture_array(
+        &mut self,
+        key_texture: u64,
+        array: bool,
+    ) {
+        if array {
+            self.texture_arrays.insert(key_texture);
+        } else {
+            self.texture_arrays.remove(&key_texture);
+        }
+    }
+
+    /// 当前纹理是否为记录的纹理数组
+    fn texture_is_array(&self) -> bool {
+        self.texture.as_ref().map(|texture| self.texture_arrays.contains(texture.key())).unwrap_or(false)
+    }
+
+    pub fn record_sampler(
+        &mut self,
+        key_sampler: SamplerDesc,
+        sampler: Handle<SamplerRes>,
+    ) {
+        // log::warn!("record_sampler {:?}", key_sampler);
+        self.sampler_frames.insert(key_sampler.clone(), self.frame);
+        self.samplers.insert(key_sampler, sampler);
+    }
+
+    /// 上传 GPU 蒙皮的常驻网格, 顶点布局见 KeySpineShader::Skinned; 替换同 key 网格时已记录的绘制仍使用旧网格
+    pub fn mesh(
+        &mut self,
+        key_mesh: u64,
+        mesh: SpineMesh,
+    ) {
+        self.dirty = true;
+        self.meshes.insert(key_mesh, Arc::new(mesh));
+    }
+
+    pub fn remove_mesh(
+        &mut self,
+        key_mesh: u64,
+    ) {
+        self.dirty = true;
+        self.meshes.remove(&key_mesh);
+    }
+
+    /// 设置后续 Skinned 绘制的骨骼矩阵, 每个骨骼 8 个 f32: a, b, worldX, 0, c, d, worldY, 0;
+    /// 超过 BindBones::MAX 个骨骼的部分被忽略, 网格中更大的骨骼序号按最后一个骨骼处理
+    pub fn bones(
+        &mut self,
+        bones: Vec<f32>,
+    ) {
+        self.dirty = true;
+        self.bones_param.push(bones);
+    }
+
+    pub fn remove_texture(
+        &mut self,
+        key_texture: u64,
+    ) {
+        self.texture_frames.remove(&key_texture);
+        self.textures.remove(&key_texture);
+        self.texture_arrays.remove(&key_texture);
+    }
+
+    fn pipeline_key(&self, shader: KeySpineShader, blend: Option<wgpu::BlendState>) -> KeySpinePipeline {
+        // let unclipped_depth = renderopt.features & wgpu::Features::DEPTH_CLIP_CONTROL == wgpu::Features::DEPTH_CLIP_CONTROL;
+
+        KeySpinePipeline {
+            key_shader: shader,
+            key_state: KeyRenderPipelineState {
+                primitive: PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    front_face: wgpu::FrontFace::Ccw,
+                    
+                    // #[cfg(not(target_arch = "wasm32"))]
+                    // unclipped_depth: true,
+
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                multisample: wgpu::MultisampleState { count: self.sample_count, mask: !0, alpha_to_coverage_enabled: false },
+                depth_stencil: None,
+                target_state: Some(wgpu::ColorTargetState { format: self.target_format, blend, write_mask: wgpu::ColorWrites::ALL }),
+            },
+            packing: if shader == KeySpineShader::Skinned { ESpineVertexPacking::Float32 } else { self.vertex_packing },
+        }
+    }
+    pub fn draw(
+        &mut self,
+        vertices: Vec<f32>,
+        indices: Option<Vec<u16>>,
+        vertices_len: u32,
+        indices_len: u32,
+        _renderopt: &RenderOptions,
+    ) {
+        let index = self.draw_count;
+        self.draw_count += 1;
+
+        let shader = if let Some(shader) = &self.shader {
+            shader
+        } else {
+            self.errors.push(SpineRenderError::MissingShader { draw: index });
+            return;
+        };
+
+        let indices = if let Some(indices) = indices {
+            Some(indices)
+        } else {
+            None
+        };
+
+        if self.uniform_param.len() == 0 {
+            self.errors.push(SpineRenderError::MissingUniform { draw: index });
+            return;
+        }
+
+        match shader {
+            KeySpineShader::Colored => {
+            },
+            KeySpineShader::Skinned => {
+                // 蒙皮网格须经 draw_skinned 绘制
+                self.errors.push(SpineRenderError::MissingMesh { draw: index });
+                return;
+            },
+            KeySpineShader::ColoredTextured | KeySpineShader::TwoColoredTextured | KeySpineShader::TwoColoredTexturedArray => {
+                // 缺少纹理时保留 Draw, 由 drawlist 使用占位纹理或报告 MissingTexture
+                if self.texture.is_some() && self.sampler.is_none() {
+                    self.errors.push(SpineRenderError::MissingSampler { draw: index, shader: *shader });
+                    return;
+                }
+            },
+        };
+
+        let blend = if self.enableblend {
+            Some(self.blend.clone())
+        } else {
+            None
+        };
+
+        let mut key = self.pipeline_key(*shader, blend);
+        key.packing = key.packing.fit(*shader, &vertices);
+        let vertex_count = vertices_len.min(vertices.len() as u32 / shader.vertices_floats_per_element());
+        let vertices = key.packing.pack(*shader, &vertices);
+
+        let draw = SpineDraw {
+            index,
+            bind_key: self.uniform_param.len() - 1,
+            mesh: None,
+            bones_key: 0,
+            vertices,
+            indices,
+            vertex_count,
+            indiceslen: indices_len,
+            texture: self.texture.clone(),
+            sampler: self.sampler.clone(),
+            shader: shader.clone(),
+            pipeline: key,
+            array: self.texture_is_array(),
+        };
+
+        self.dirty = true;
+        self.draws.push(draw);
+        // log::warn!("Draws: {:?}", self.draws.len());
+    }
+    /// 以当前 Uniform、骨骼矩阵与纹理绘制常驻网格, 使用 Skinned 着色器
+    pub fn draw_skinned(
+        &mut self,
+        key_mesh: u64,
+    ) {
+        let index = self.draw_count;
+        self.draw_count += 1;
+
+        let mesh = if let Some(mesh) = self.meshes.get(&key_mesh) {
+            mesh.clone()
+        } else {
+            self.errors.push(SpineRenderError::MissingMesh { draw: index });
+            return;
+        };
+        if self.uniform_param.len() == 0 {
+            self.errors.push(SpineRenderError::MissingUniform { draw: index });
+            return;
+        }
+        if self.bones_param.len() == 0 {
+            self.errors.push(SpineRenderError::MissingBones { draw: index });
+            return;
+        }
+        let shader = KeySpineShader::Skinned;
+        if self.texture.is_some() && self.sampler.is_none() {
+            self.errors.push(SpineRenderError::MissingSampler { draw: index, shader });
+            return;
+        }
+
+        let blend = if self.enableblend {
+            Some(self.blend.clone())
+        } else {
+            None
+        };
+
+        let draw = SpineDraw {
+            index,
+            bind_key: self.uniform_param.len() - 1,
+            mesh: Some(mesh),
+            bones_key: self.bones_param.len() - 1,
+            vertices: vec![],
+            indices: None,
+            vertex_count: 0,
+            indiceslen: 0,
+            texture: self.texture.clone(),
+            sampler: self.sampler.clone(),
+            shader,
+            pipeline: self.pipeline_key(shader, blend),
+            array: self.texture_is_array(),
+        };
+
+        self.dirty = true;
+        self.draws.push(draw);
+    }
+}
@@ -0,0 +1,11 @@
+#version 450
+
+#define SHADER_NAME vertex:Blit
+
+layout(location = 0) out vec2 v_texCoords;
+
+void main() {
+    vec2 uv = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2));
+    v_texCoords = uv;
+    gl_Position = vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+}
\ No newline at end of file
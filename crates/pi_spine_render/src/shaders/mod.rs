@@ -1,326 +1,630 @@
-
-
-use std::hash::{Hash, Hasher};
-
-use pi_assets::{asset::{Handle, GarbageEmpty}, mgr::AssetMgr};
-use pi_hash::DefaultHasher;
-use pi_render::{renderer::pipeline::KeyRenderPipelineState, rhi::{device::RenderDevice, asset::RenderRes, pipeline::RenderPipeline, bind_group_layout::BindGroupLayout}, asset::ASSET_SIZE_FOR_UNKOWN};
-use pi_share::Share;
-
-use crate::binds::param::BindParam;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum EKeySpineSet {
-    Param,
-    Texture,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum KeySpineShader {
-    Colored = 0,
-    ColoredTextured,
-    TwoColoredTextured,
-}
-impl KeySpineShader {
-    pub fn key(&self) -> String {
-        match self {
-            Self::Colored => String::from("Colored"),
-            Self::ColoredTextured => String::from("ColoredTextured"),
-            Self::TwoColoredTextured => String::from("TwoColoredTextured"),
-        }
-    }
-    pub fn vertices_bytes_per_element(&self) -> u32 {
-        match self {
-            KeySpineShader::Colored => (2 + 4) * 4,
-            KeySpineShader::ColoredTextured => (2 + 4 + 2) * 4,
-            KeySpineShader::TwoColoredTextured => (2 + 4 + 2 + 4) * 4,
-        }
-    }
-    pub fn attributes(&self) -> Vec<wgpu::VertexAttribute> {
-        match self {
-            KeySpineShader::Colored => {
-                vec![
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 08, shader_location: 1, },
-                ]
-            },
-            KeySpineShader::ColoredTextured => {
-                vec![
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 08, shader_location: 1, },
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 24, shader_location: 2, },
-                ]
-            },
-            KeySpineShader::TwoColoredTextured => {
-                vec![
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 08, shader_location: 1, },
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 24, shader_location: 2, },
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 32, shader_location: 3, },
-                ]
-            },
-        }
-    }
-    pub fn bind_group_layout(&self, device: &RenderDevice) -> BindGroupLayout {
-        match self {
-            KeySpineShader::Colored => {
-                device.create_bind_group_layout(
-                    &wgpu::BindGroupLayoutDescriptor {
-                        label: None,
-                        entries: &[
-                            // Param
-                            BindParam::layout_entry(),
-                        ],
-                    }
-                )
-            },
-            _ => {
-                device.create_bind_group_layout(
-                    &wgpu::BindGroupLayoutDescriptor {
-                        label: None,
-                        entries: &[
-                            // Param
-                            BindParam::layout_entry(),
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 1,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Texture {
-                                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                                    view_dimension: wgpu::TextureViewDimension::D2,
-                                    multisampled: false,
-                                },
-                                count: None,
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 2,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                                count: None,
-                            },
-                        ]
-                    }
-                )
-            },
-        }
-    }
-    pub fn shader(&self, device: &RenderDevice) -> SpineShader {
-        match self {
-            Self::Colored => {
-                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some((self.key() + "-VS").as_str()),
-                    source: wgpu::ShaderSource::Glsl {
-                        shader: std::borrow::Cow::Borrowed(include_str!("./colored.vert")),
-                        stage: naga::ShaderStage::Vertex,
-                        defines: naga::FastHashMap::default(),
-                    },
-                });
-        
-                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some((self.key() + "-FS").as_str()),
-                    source: wgpu::ShaderSource::Glsl {
-                        shader: std::borrow::Cow::Borrowed(include_str!("./colored.frag")),
-                        stage: naga::ShaderStage::Fragment,
-                        defines: naga::FastHashMap::default(),
-                    },
-                });
-        
-                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
-            },
-            Self::ColoredTextured => {
-                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some((self.key() + "-VS").as_str()),
-                    source: wgpu::ShaderSource::Glsl {
-                        shader: std::borrow::Cow::Borrowed(include_str!("./colored_textured.vert")),
-                        stage: naga::ShaderStage::Vertex,
-                        defines: naga::FastHashMap::default(),
-                    },
-                });
-        
-                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some((self.key() + "-FS").as_str()),
-                    source: wgpu::ShaderSource::Glsl {
-                        shader: std::borrow::Cow::Borrowed(include_str!("./colored_textured.frag")),
-                        stage: naga::ShaderStage::Fragment,
-                        defines: naga::FastHashMap::default(),
-                    },
-                });
-        
-                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
-            },
-            Self::TwoColoredTextured => {
-                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some((self.key() + "-VS").as_str()),
-                    source: wgpu::ShaderSource::Glsl {
-                        shader: std::borrow::Cow::Borrowed(include_str!("./two_colored_textured.vert")),
-                        stage: naga::ShaderStage::Vertex,
-                        defines: naga::FastHashMap::default(),
-                    },
-                });
-        
-                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some((self.key() + "-FS").as_str()),
-                    source: wgpu::ShaderSource::Glsl {
-                        shader: std::borrow::Cow::Borrowed(include_str!("./two_colored_textured.frag")),
-                        stage: naga::ShaderStage::Fragment,
-                        defines: naga::FastHashMap::default(),
-                    },
-                });
-        
-                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
-            },
-        }
-    }
-}
-
-pub struct SpineShader {
-    pub vs: wgpu::ShaderModule,
-    pub vs_point: &'static str,
-    pub fs: wgpu::ShaderModule,
-    pub fs_point: &'static str,
-}
-
-pub struct SingleSpineShaderPool {
-    pub colored: SpineShader,
-    pub colored_textured: SpineShader,
-    pub two_colored_textured: SpineShader,
-}
-impl SingleSpineShaderPool {
-    pub fn new(device: &RenderDevice) -> Self {
-        Self {
-            colored: KeySpineShader::Colored.shader(device),
-            colored_textured: KeySpineShader::ColoredTextured.shader(device),
-            two_colored_textured: KeySpineShader::TwoColoredTextured.shader(device),
-        }
-    }
-    fn shader(&self, key: &KeySpineShader) -> &SpineShader {
-        match key {
-            KeySpineShader::Colored => &self.colored,
-            KeySpineShader::ColoredTextured => &self.colored_textured,
-            KeySpineShader::TwoColoredTextured => &self.two_colored_textured,
-        }
-    }
-}
-
-pub struct SingleSpineBindGroupLayout {
-    pub colored: BindGroupLayout,
-    pub colored_textured: BindGroupLayout,
-    pub two_colored_textured: BindGroupLayout,
-}
-impl SingleSpineBindGroupLayout {
-    pub fn new(device: &RenderDevice) -> Self {
-        Self {
-            colored: KeySpineShader::Colored.bind_group_layout(device),
-            colored_textured:  KeySpineShader::ColoredTextured.bind_group_layout(device),
-            two_colored_textured:  KeySpineShader::TwoColoredTextured.bind_group_layout(device),
-        }
-    }
-    pub fn value<'a>(&'a self, key: &KeySpineShader) -> Vec<&'a wgpu::BindGroupLayout> {
-        match key {
-            KeySpineShader::Colored => vec![&self.colored],
-            KeySpineShader::ColoredTextured => vec![&self.colored_textured],
-            KeySpineShader::TwoColoredTextured => vec![&self.two_colored_textured],
-        }
-    }
-}
-
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
-pub struct KeySpinePipeline {
-    pub key_shader: KeySpineShader,
-    pub key_state: KeyRenderPipelineState,
-}
-impl KeySpinePipeline {
-    pub fn as_u64(&self) -> u64 {
-        let mut hasher = DefaultHasher::default();
-        self.hash(&mut hasher);
-        hasher.finish()
-    }
-}
-
-pub struct SingleSpinePipelinePool {
-    shaders: SingleSpineShaderPool,
-    bind_group_layouts: SingleSpineBindGroupLayout,
-    asset_mgr: Share<AssetMgr<RenderRes<RenderPipeline>>>
-}
-impl SingleSpinePipelinePool {
-    pub fn new(device: &RenderDevice) -> Self {
-        Self {
-            shaders: SingleSpineShaderPool::new(device),
-            bind_group_layouts: SingleSpineBindGroupLayout::new(device),
-            asset_mgr: AssetMgr::<RenderRes::<RenderPipeline>>::new(GarbageEmpty(), false, 1 * 1024, 60 * 1000),
-        }
-    }
-    pub fn get(
-        &self,
-        device: &RenderDevice, 
-        key: &KeySpinePipeline,
-    ) -> Option<Handle<RenderRes<RenderPipeline>>> {
-        let key_u64 = key.as_u64();
-        if let Some(pipeline) = self.asset_mgr.get(&key_u64) {
-            Some(pipeline)
-        } else {
-            let pipeline = self.pipeline(device, key);
-            self.asset_mgr.insert(key_u64, pipeline).ok()
-        }
-    }
-    fn pipeline(
-        &self,
-        device: &RenderDevice, 
-        key: &KeySpinePipeline,
-    ) -> RenderRes<RenderPipeline> {
-        let key_shader = &key.key_shader;
-        let state = &key.key_state;
-        let array_stride = key_shader.vertices_bytes_per_element();
-        let attributes = key_shader.attributes();
-        let shader = self.shaders.shader(key_shader);
-        let bind_group_layouts = self.bind_group_layouts.value(key_shader);
-        
-        let vertex_layouts = vec![
-            wgpu::VertexBufferLayout {
-                array_stride: array_stride as u64,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: &attributes,
-            },
-        ];
-        let vs_state = wgpu::VertexState {
-            module: &shader.vs,
-            entry_point: "main",
-            buffers: &vertex_layouts,
-        };
-        let fs_state = wgpu::FragmentState {
-            module: &shader.fs,
-            entry_point: "main",
-            targets: &[state.target_state.clone()],
-        };
-        let pipeline_layout = device.create_pipeline_layout(
-            &wgpu::PipelineLayoutDescriptor {
-                label: None,
-                bind_group_layouts: &bind_group_layouts,
-                push_constant_ranges: &[],
-            }
-        );
-
-        let depth_stencil = if let Some(depth_stencil) = &state.depth_stencil {
-            Some(depth_stencil.depth_stencil_state())
-        } else {
-            None
-        };
-        let pipeline = device.create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: None,
-                layout: Some(&pipeline_layout),
-                vertex: vs_state,
-                fragment: Some(fs_state),
-                primitive: state.primitive.clone(),
-                depth_stencil,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false
-                },
-                multiview: None,
-            }
-        );
-
-        RenderRes::new(pipeline, ASSET_SIZE_FOR_UNKOWN)
-    }
-}
+
+
+use std::hash::{Hash, Hasher};
+
+use pi_assets::{asset::{Handle, GarbageEmpty}, mgr::AssetMgr};
+use pi_hash::DefaultHasher;
+use pi_render::{renderer::pipeline::KeyRenderPipelineState, rhi::{device::RenderDevice, asset::RenderRes, pipeline::RenderPipeline, bind_group_layout::BindGroupLayout}, asset::ASSET_SIZE_FOR_UNKOWN};
+use pi_share::Share;
+
+use crate::binds::param::{BindParam, BindBones};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EKeySpineSet {
+    Param,
+    Texture,
+}
+
+/// 顶点数据的压缩布局; 位置始终为 Float32x2, 命令中的顶点仍为 f32, 上传前转换
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ESpineVertexPacking {
+    #[default]
+    Float32,
+    /// 颜色 Unorm8x4, uv Unorm16x2; uv 超出 [0, 1] 的绘制改用 Float16Uv
+    Unorm16Uv,
+    /// 颜色 Unorm8x4, uv Float16x2
+    Float16Uv,
+}
+impl ESpineVertexPacking {
+    fn color(color: &[f32], result: &mut Vec<u8>) {
+        color.iter().for_each(|v| result.push((v.clamp(0., 1.) * 255.).round() as u8));
+    }
+    fn uv(&self, uv: &[f32], result: &mut Vec<u8>) {
+        uv.iter().for_each(|v| {
+            let bits = match self {
+                Self::Float16Uv => half::f16::from_f32(*v).to_bits(),
+                _ => (v.clamp(0., 1.) * 65535.).round() as u16,
+            };
+            result.extend_from_slice(&bits.to_le_bytes());
+        });
+    }
+    /// uv 超出 [0, 1] 时 Unorm16Uv 无法表示, 改用 Float16Uv
+    pub fn fit(&self, shader: KeySpineShader, vertices: &[f32]) -> Self {
+        let floats = shader.vertices_floats_per_element() as usize;
+        if *self != Self::Unorm16Uv || floats < 8 {
+            return *self;
+        }
+        let outside = vertices.chunks_exact(floats).any(|vertex| {
+            vertex[6..8].iter().any(|v| !(0. ..=1.).contains(v))
+        });
+        if outside { Self::Float16Uv } else { *self }
+    }
+    /// 将 f32 顶点转换为 shader 对应的压缩布局; Skinned 不压缩
+    pub fn pack(&self, shader: KeySpineShader, vertices: &[f32]) -> Vec<u8> {
+        if *self == Self::Float32 || shader == KeySpineShader::Skinned {
+            return bytemuck::cast_slice(vertices).to_vec();
+        }
+        let floats = shader.vertices_floats_per_element() as usize;
+        let mut result = Vec::with_capacity(vertices.len() / floats * shader.packed_bytes_per_element(*self) as usize);
+        vertices.chunks_exact(floats).for_each(|vertex| {
+            result.extend_from_slice(bytemuck::cast_slice(&vertex[0..2]));
+            Self::color(&vertex[2..6], &mut result);
+            if floats >= 8 {
+                self.uv(&vertex[6..8], &mut result);
+            }
+            if floats >= 12 {
+                Self::color(&vertex[8..12], &mut result);
+            }
+            if floats >= 13 {
+                result.extend_from_slice(bytemuck::cast_slice(&vertex[12..13]));
+            }
+        });
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeySpineShader {
+    Colored = 0,
+    ColoredTextured,
+    TwoColoredTextured,
+    /// GPU 蒙皮: 常驻网格顶点为骨骼空间坐标, 由 set 1 的骨骼矩阵变换; 着色同 TwoColoredTextured
+    Skinned,
+    /// 同 TwoColoredTextured, 纹理为 D2Array, 顶点末尾为所在页的层序号;
+    /// 同尺寸的多页放入同一纹理数组后, 跨页的绘制无需切换 BindGroup, 可合并为一次 Draw
+    TwoColoredTexturedArray,
+}
+impl KeySpineShader {
+    pub fn key(&self) -> String {
+        match self {
+            Self::Colored => String::from("Colored"),
+            Self::ColoredTextured => String::from("ColoredTextured"),
+            Self::TwoColoredTextured => String::from("TwoColoredTextured"),
+            Self::Skinned => String::from("Skinned"),
+            Self::TwoColoredTexturedArray => String::from("TwoColoredTexturedArray"),
+        }
+    }
+    pub fn vertices_bytes_per_element(&self) -> u32 {
+        match self {
+            KeySpineShader::Colored => (2 + 4) * 4,
+            KeySpineShader::ColoredTextured => (2 + 4 + 2) * 4,
+            KeySpineShader::TwoColoredTextured => (2 + 4 + 2 + 4) * 4,
+            KeySpineShader::Skinned => (4 + 4 + 4 + 4 + 2 + 4 + 4) * 4,
+            KeySpineShader::TwoColoredTexturedArray => (2 + 4 + 2 + 4 + 1) * 4,
+        }
+    }
+    pub fn vertices_floats_per_element(&self) -> u32 {
+        self.vertices_bytes_per_element() / 4
+    }
+    /// 压缩布局下每个顶点的字节数
+    pub fn packed_bytes_per_element(&self, packing: ESpineVertexPacking) -> u32 {
+        match (self, packing) {
+            (_, ESpineVertexPacking::Float32) | (KeySpineShader::Skinned, _) => self.vertices_bytes_per_element(),
+            (KeySpineShader::Colored, _) => 8 + 4,
+            (KeySpineShader::ColoredTextured, _) => 8 + 4 + 4,
+            (KeySpineShader::TwoColoredTextured, _) => 8 + 4 + 4 + 4,
+            (KeySpineShader::TwoColoredTexturedArray, _) => 8 + 4 + 4 + 4 + 4,
+        }
+    }
+    /// 压缩布局的顶点属性, shader 输入类型不变
+    pub fn packed_attributes(&self, packing: ESpineVertexPacking) -> Vec<wgpu::VertexAttribute> {
+        let uv = match packing {
+            ESpineVertexPacking::Float32 => return self.attributes(),
+            ESpineVertexPacking::Unorm16Uv => wgpu::VertexFormat::Unorm16x2,
+            ESpineVertexPacking::Float16Uv => wgpu::VertexFormat::Float16x2,
+        };
+        match self {
+            KeySpineShader::Colored => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Unorm8x4, offset: 08, shader_location: 1, },
+                ]
+            },
+            KeySpineShader::ColoredTextured => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Unorm8x4, offset: 08, shader_location: 1, },
+                    wgpu::VertexAttribute { format: uv, offset: 12, shader_location: 2, },
+                ]
+            },
+            KeySpineShader::TwoColoredTextured => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Unorm8x4, offset: 08, shader_location: 1, },
+                    wgpu::VertexAttribute { format: uv, offset: 12, shader_location: 2, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Unorm8x4, offset: 16, shader_location: 3, },
+                ]
+            },
+            KeySpineShader::TwoColoredTexturedArray => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Unorm8x4, offset: 08, shader_location: 1, },
+                    wgpu::VertexAttribute { format: uv, offset: 12, shader_location: 2, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Unorm8x4, offset: 16, shader_location: 3, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32, offset: 20, shader_location: 4, },
+                ]
+            },
+            KeySpineShader::Skinned => self.attributes(),
+        }
+    }
+    pub fn attributes(&self) -> Vec<wgpu::VertexAttribute> {
+        match self {
+            KeySpineShader::Colored => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 08, shader_location: 1, },
+                ]
+            },
+            KeySpineShader::ColoredTextured => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 08, shader_location: 1, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 24, shader_location: 2, },
+                ]
+            },
+            KeySpineShader::TwoColoredTextured => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 08, shader_location: 1, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 24, shader_location: 2, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 32, shader_location: 3, },
+                ]
+            },
+            KeySpineShader::Skinned => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 16, shader_location: 1, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 32, shader_location: 2, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 48, shader_location: 3, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 64, shader_location: 4, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 72, shader_location: 5, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 88, shader_location: 6, },
+                ]
+            },
+            KeySpineShader::TwoColoredTexturedArray => {
+                vec![
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 00, shader_location: 0, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 08, shader_location: 1, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 24, shader_location: 2, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 32, shader_location: 3, },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32, offset: 48, shader_location: 4, },
+                ]
+            },
+        }
+    }
+    pub fn bind_group_layout(&self, device: &RenderDevice) -> BindGroupLayout {
+        match self {
+            KeySpineShader::Colored => {
+                device.create_bind_group_layout(
+                    &wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[
+                            // Param
+                            BindParam::layout_entry(),
+                        ],
+                    }
+                )
+            },
+            _ => {
+                let view_dimension = if *self == KeySpineShader::TwoColoredTexturedArray {
+                    wgpu::TextureViewDimension::D2Array
+                } else {
+                    wgpu::TextureViewDimension::D2
+                };
+                device.create_bind_group_layout(
+                    &wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[
+                            // Param
+                            BindParam::layout_entry(),
+                            wgpu::BindGroupLayoutEntry {
+                                binding: 1,
+                                visibility: wgpu::ShaderStages::FRAGMENT,
+                                ty: wgpu::BindingType::Texture {
+                                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                    view_dimension,
+                                    multisampled: false,
+                                },
+                                count: None,
+                            },
+                            wgpu::BindGroupLayoutEntry {
+                                binding: 2,
+                                visibility: wgpu::ShaderStages::FRAGMENT,
+                                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                                count: None,
+                            },
+                        ]
+                    }
+                )
+            },
+        }
+    }
+    pub fn shader(&self, device: &RenderDevice) -> SpineShader {
+        match self {
+            Self::Colored => {
+                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-VS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./colored.vert")),
+                        stage: naga::ShaderStage::Vertex,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-FS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./colored.frag")),
+                        stage: naga::ShaderStage::Fragment,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
+            },
+            Self::ColoredTextured => {
+                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-VS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./colored_textured.vert")),
+                        stage: naga::ShaderStage::Vertex,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-FS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./colored_textured.frag")),
+                        stage: naga::ShaderStage::Fragment,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
+            },
+            Self::TwoColoredTextured => {
+                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-VS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./two_colored_textured.vert")),
+                        stage: naga::ShaderStage::Vertex,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-FS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./two_colored_textured.frag")),
+                        stage: naga::ShaderStage::Fragment,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
+            },
+            Self::Skinned => {
+                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-VS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./skinned.vert")),
+                        stage: naga::ShaderStage::Vertex,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-FS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./two_colored_textured.frag")),
+                        stage: naga::ShaderStage::Fragment,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
+            },
+            Self::TwoColoredTexturedArray => {
+                let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-VS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./two_colored_textured_array.vert")),
+                        stage: naga::ShaderStage::Vertex,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some((self.key() + "-FS").as_str()),
+                    source: wgpu::ShaderSource::Glsl {
+                        shader: std::borrow::Cow::Borrowed(include_str!("./two_colored_textured_array.frag")),
+                        stage: naga::ShaderStage::Fragment,
+                        defines: naga::FastHashMap::default(),
+                    },
+                });
+        
+                SpineShader { vs, vs_point: "main", fs, fs_point: "main"  }
+            },
+        }
+    }
+    /// 蒙皮骨骼矩阵的 set 1 布局
+    pub fn bones_bind_group_layout(device: &RenderDevice) -> BindGroupLayout {
+        device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    BindBones::layout_entry(),
+                ],
+            }
+        )
+    }
+}
+
+pub struct SpineShader {
+    pub vs: wgpu::ShaderModule,
+    pub vs_point: &'static str,
+    pub fs: wgpu::ShaderModule,
+    pub fs_point: &'static str,
+}
+
+pub struct SingleSpineShaderPool {
+    pub colored: SpineShader,
+    pub colored_textured: SpineShader,
+    pub two_colored_textured: SpineShader,
+    pub skinned: SpineShader,
+    pub two_colored_textured_array: SpineShader,
+}
+impl SingleSpineShaderPool {
+    pub fn new(device: &RenderDevice) -> Self {
+        Self {
+            colored: KeySpineShader::Colored.shader(device),
+            colored_textured: KeySpineShader::ColoredTextured.shader(device),
+            two_colored_textured: KeySpineShader::TwoColoredTextured.shader(device),
+            skinned: KeySpineShader::Skinned.shader(device),
+            two_colored_textured_array: KeySpineShader::TwoColoredTexturedArray.shader(device),
+        }
+    }
+    fn shader(&self, key: &KeySpineShader) -> &SpineShader {
+        match key {
+            KeySpineShader::Colored => &self.colored,
+            KeySpineShader::ColoredTextured => &self.colored_textured,
+            KeySpineShader::TwoColoredTextured => &self.two_colored_textured,
+            KeySpineShader::Skinned => &self.skinned,
+            KeySpineShader::TwoColoredTexturedArray => &self.two_colored_textured_array,
+        }
+    }
+}
+
+pub struct SingleSpineBindGroupLayout {
+    pub colored: BindGroupLayout,
+    pub colored_textured: BindGroupLayout,
+    pub two_colored_textured: BindGroupLayout,
+    pub bones: BindGroupLayout,
+    pub two_colored_textured_array: BindGroupLayout,
+}
+impl SingleSpineBindGroupLayout {
+    pub fn new(device: &RenderDevice) -> Self {
+        Self {
+            colored: KeySpineShader::Colored.bind_group_layout(device),
+            colored_textured:  KeySpineShader::ColoredTextured.bind_group_layout(device),
+            two_colored_textured:  KeySpineShader::TwoColoredTextured.bind_group_layout(device),
+            bones: KeySpineShader::bones_bind_group_layout(device),
+            two_colored_textured_array: KeySpineShader::TwoColoredTexturedArray.bind_group_layout(device),
+        }
+    }
+    pub fn value<'a>(&'a self, key: &KeySpineShader) -> Vec<&'a wgpu::BindGroupLayout> {
+        match key {
+            KeySpineShader::Colored => vec![&self.colored],
+            KeySpineShader::ColoredTextured => vec![&self.colored_textured],
+            KeySpineShader::TwoColoredTextured => vec![&self.two_colored_textured],
+            // set 0 与 ColoredTextured 相同, 复用其 BindGroup
+            KeySpineShader::Skinned => vec![&self.colored_textured, &self.bones],
+            KeySpineShader::TwoColoredTexturedArray => vec![&self.two_colored_textured_array],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct KeySpinePipeline {
+    pub key_shader: KeySpineShader,
+    pub key_state: KeyRenderPipelineState,
+    pub packing: ESpineVertexPacking,
+}
+impl KeySpinePipeline {
+    pub fn as_u64(&self) -> u64 {
+        let mut hasher = DefaultHasher::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+pub struct SingleSpinePipelinePool {
+    shaders: SingleSpineShaderPool,
+    bind_group_layouts: SingleSpineBindGroupLayout,
+    asset_mgr: Share<AssetMgr<RenderRes<RenderPipeline>>>,
+    /// 累计创建的管线数
+    created: u64,
+}
+impl SingleSpinePipelinePool {
+    pub const CACHE_CAPACITY: usize = 1 * 1024;
+    pub fn new(device: &RenderDevice) -> Self {
+        Self {
+            shaders: SingleSpineShaderPool::new(device),
+            bind_group_layouts: SingleSpineBindGroupLayout::new(device),
+            asset_mgr: AssetMgr::<RenderRes::<RenderPipeline>>::new(GarbageEmpty(), false, Self::CACHE_CAPACITY, 60 * 1000),
+            created: 0,
+        }
+    }
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+    /// 缓存当前占用
+    pub fn cache_size(&self) -> usize {
+        self.asset_mgr.size()
+    }
+    pub fn get(
+        &mut self,
+        device: &RenderDevice, 
+        key: &KeySpinePipeline,
+    ) -> Option<Handle<RenderRes<RenderPipeline>>> {
+        let key_u64 = key.as_u64();
+        if let Some(pipeline) = self.asset_mgr.get(&key_u64) {
+            Some(pipeline)
+        } else {
+            let pipeline = self.pipeline(device, key);
+            self.created += 1;
+            self.asset_mgr.insert(key_u64, pipeline).ok()
+        }
+    }
+    fn pipeline(
+        &self,
+        device: &RenderDevice, 
+        key: &KeySpinePipeline,
+    ) -> RenderRes<RenderPipeline> {
+        let key_shader = &key.key_shader;
+        let state = &key.key_state;
+        let array_stride = key_shader.packed_bytes_per_element(key.packing);
+        let attributes = key_shader.packed_attributes(key.packing);
+        let shader = self.shaders.shader(key_shader);
+        let bind_group_layouts = self.bind_group_layouts.value(key_shader);
+        
+        let vertex_layouts = vec![
+            wgpu::VertexBufferLayout {
+                array_stride: array_stride as u64,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &attributes,
+            },
+        ];
+        let vs_state = wgpu::VertexState {
+            module: &shader.vs,
+            entry_point: "main",
+            buffers: &vertex_layouts,
+        };
+        let fs_state = wgpu::FragmentState {
+            module: &shader.fs,
+            entry_point: "main",
+            targets: &[state.target_state.clone()],
+        };
+        let pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &bind_group_layouts,
+                push_constant_ranges: &[],
+            }
+        );
+
+        let depth_stencil = if let Some(depth_stencil) = &state.depth_stencil {
+            Some(depth_stencil.depth_stencil_state())
+        } else {
+            None
+        };
+        let pipeline = device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: vs_state,
+                fragment: Some(fs_state),
+                primitive: state.primitive.clone(),
+                depth_stencil,
+                multisample: state.multisample.clone(),
+                multiview: None,
+            }
+        );
+
+        RenderRes::new(pipeline, ASSET_SIZE_FOR_UNKOWN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADERS: [KeySpineShader; 5] = [
+        KeySpineShader::Colored,
+        KeySpineShader::ColoredTextured,
+        KeySpineShader::TwoColoredTextured,
+        KeySpineShader::Skinned,
+        KeySpineShader::TwoColoredTexturedArray,
+    ];
+    const PACKINGS: [ESpineVertexPacking; 3] = [ESpineVertexPacking::Float32, ESpineVertexPacking::Unorm16Uv, ESpineVertexPacking::Float16Uv];
+
+    fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+        f32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+    }
+    fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+    }
+
+    #[test]
+    fn packed_length_matches_layout() {
+        for shader in SHADERS {
+            let vertices = vec![0.5; shader.vertices_floats_per_element() as usize * 3];
+            for packing in PACKINGS {
+                let bytes = packing.pack(shader, &vertices);
+                assert_eq!(bytes.len(), 3 * shader.packed_bytes_per_element(packing) as usize, "{:?} {:?}", shader, packing);
+            }
+        }
+    }
+
+    #[test]
+    fn float32_is_unchanged() {
+        let vertices = [1.5, -2., 1., 0.5, 0., 1., 0.25, 0.75];
+        let bytes = ESpineVertexPacking::Float32.pack(KeySpineShader::ColoredTextured, &vertices);
+        assert_eq!(bytes, bytemuck::cast_slice::<f32, u8>(&vertices));
+    }
+
+    #[test]
+    fn skinned_is_never_packed() {
+        let vertices = vec![0.25; KeySpineShader::Skinned.vertices_floats_per_element() as usize];
+        let bytes = ESpineVertexPacking::Unorm16Uv.pack(KeySpineShader::Skinned, &vertices);
+        assert_eq!(bytes, bytemuck::cast_slice::<f32, u8>(&vertices));
+    }
+
+    #[test]
+    fn unorm16_uv_round_trip() {
+        let vertices = [1.5, -2., 1., 0.5, 0., 1., 0.25, 0.75];
+        let bytes = ESpineVertexPacking::Unorm16Uv.pack(KeySpineShader::ColoredTextured, &vertices);
+        assert_eq!(f32_at(&bytes, 0), 1.5);
+        assert_eq!(f32_at(&bytes, 4), -2.);
+        assert_eq!(&bytes[8..12], &[255, 128, 0, 255]);
+        assert!((u16_at(&bytes, 12) as f32 / 65535. - 0.25).abs() <= 1. / 65535.);
+        assert!((u16_at(&bytes, 14) as f32 / 65535. - 0.75).abs() <= 1. / 65535.);
+    }
+
+    #[test]
+    fn float16_uv_round_trip() {
+        let vertices = [0., 0., 1., 1., 1., 1., 2.5, -1., 0., 0., 0., 1.];
+        let bytes = ESpineVertexPacking::Float16Uv.pack(KeySpineShader::TwoColoredTextured, &vertices);
+        assert_eq!(half::f16::from_bits(u16_at(&bytes, 12)).to_f32(), 2.5);
+        assert_eq!(half::f16::from_bits(u16_at(&bytes, 14)).to_f32(), -1.);
+        assert_eq!(&bytes[16..20], &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn array_page_stays_float() {
+        let vertices = [0., 0., 1., 1., 1., 1., 0.5, 0.5, 0., 0., 0., 1., 3.];
+        let bytes = ESpineVertexPacking::Unorm16Uv.pack(KeySpineShader::TwoColoredTexturedArray, &vertices);
+        assert_eq!(f32_at(&bytes, 20), 3.);
+    }
+
+    #[test]
+    fn fit_falls_back_for_out_of_range_uv() {
+        let inside = [0., 0., 1., 1., 1., 1., 0., 1.];
+        let outside = [0., 0., 1., 1., 1., 1., 0., 1.5];
+        assert_eq!(ESpineVertexPacking::Unorm16Uv.fit(KeySpineShader::ColoredTextured, &inside), ESpineVertexPacking::Unorm16Uv);
+        assert_eq!(ESpineVertexPacking::Unorm16Uv.fit(KeySpineShader::ColoredTextured, &outside), ESpineVertexPacking::Float16Uv);
+        assert_eq!(ESpineVertexPacking::Float32.fit(KeySpineShader::ColoredTextured, &outside), ESpineVertexPacking::Float32);
+        assert_eq!(ESpineVertexPacking::Unorm16Uv.fit(KeySpineShader::Colored, &[0., 0., 2., 2., 2., 2.]), ESpineVertexPacking::Unorm16Uv);
+    }
+}
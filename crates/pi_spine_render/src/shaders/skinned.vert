@@ -0,0 +1,47 @@
+#version 450
+
+#define SHADER_NAME vertex:Skinned
+
+// 每个顶点最多 4 个骨骼影响, 每个影响为骨骼序号、权重及该骨骼空间内的坐标
+layout(location = 0) in vec4 a_bones;
+layout(location = 1) in vec4 a_weights;
+layout(location = 2) in vec4 a_local01;
+layout(location = 3) in vec4 a_local23;
+layout(location = 4) in vec2 a_texCoords;
+layout(location = 5) in vec4 a_color;
+layout(location = 6) in vec4 a_color2;
+
+layout(location = 0) out vec4 v_light;
+layout(location = 1) out vec4 v_dark;
+layout(location = 2) out vec2 v_texCoords;
+
+layout(set = 0, binding = 0) uniform Param {
+    mat4 u_projTrans;
+    vec4 u_maskflag;
+    vec4 u_visibility;
+};
+
+// 每个骨骼两行: (a, b, worldX, 0), (c, d, worldY, 0)
+layout(set = 1, binding = 0) uniform Bones {
+    vec4 u_bones[256];
+};
+
+// 骨骼序号截到 BindBones::MAX - 1 以内, 避免越界读取
+vec2 skin(float bone, vec2 local) {
+    int i = clamp(int(bone), 0, 127) * 2;
+    vec3 p = vec3(local, 1.);
+    return vec2(dot(u_bones[i].xyz, p), dot(u_bones[i + 1].xyz, p));
+}
+
+void main() {
+    v_light = a_color;
+    v_dark = a_color2;
+    v_texCoords = a_texCoords;
+    vec2 position = skin(a_bones.x, a_local01.xy) * a_weights.x
+        + skin(a_bones.y, a_local01.zw) * a_weights.y
+        + skin(a_bones.z, a_local23.xy) * a_weights.z
+        + skin(a_bones.w, a_local23.zw) * a_weights.w;
+    vec4 pos = u_projTrans * vec4(position, 0., 1.);
+    pos.z = (pos.z + pos.w) * 0.5;
+    gl_Position = pos;
+}
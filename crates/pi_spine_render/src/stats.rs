@@ -0,0 +1,74 @@
+use bevy_ecs::prelude::Resource;
+use pi_hash::XHashMap;
+
+use crate::KeySpineRenderer;
+
+/// 一帧内的绘制统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpineFrameStats {
+    /// 绘制调用数, 含清屏矩形
+    pub draw_calls: u32,
+    pub vertices: u64,
+    pub indices: u64,
+    /// 写入的顶点, 索引与 Uniform 字节数
+    pub upload_bytes: u64,
+    pub bind_groups_created: u32,
+    pub bind_group_hits: u32,
+    pub pipelines_created: u32,
+}
+impl SpineFrameStats {
+    pub fn add(&mut self, other: &Self) {
+        self.draw_calls += other.draw_calls;
+        self.vertices += other.vertices;
+        self.indices += other.indices;
+        self.upload_bytes += other.upload_bytes;
+        self.bind_groups_created += other.bind_groups_created;
+        self.bind_group_hits += other.bind_group_hits;
+        self.pipelines_created += other.pipelines_created;
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpineRendererStats {
+    pub frame: SpineFrameStats,
+    /// 本帧是否重绘, 未变化的 Atlas 目标为 false
+    pub active: bool,
+    /// 输出区域的宽高
+    pub target: Option<(u32, u32)>,
+    /// 记录的纹理数及其字节数
+    pub textures: usize,
+    pub texture_bytes: usize,
+}
+
+/// 当前占用与 SpineAssetConfig 中的预算
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpineBudgetStats {
+    pub uniform_used: u32,
+    pub uniform_capacity: u32,
+    pub uniform_budget: u32,
+    pub vertex_arena_used: usize,
+    pub vertex_arena_capacity: usize,
+    pub vertex_arena_budget: u32,
+    pub index_arena_used: usize,
+    pub index_arena_capacity: usize,
+    pub index_arena_budget: u32,
+    pub bind_group_cache: usize,
+    pub bind_group_budget: usize,
+    pub pipeline_cache: usize,
+    pub pipeline_budget: usize,
+    /// 纹理与采样器 AssetMgr 的占用字节数及容量
+    pub texture_cache: usize,
+    pub texture_capacity: usize,
+    pub sampler_cache: usize,
+    pub sampler_capacity: usize,
+}
+
+/// 每帧在 sys_spine_render_apply 中更新
+#[derive(Debug, Default, Resource)]
+pub struct SpineRenderStats {
+    /// 已统计的帧数
+    pub frame: u64,
+    pub renderers: XHashMap<KeySpineRenderer, SpineRendererStats>,
+    pub total: SpineFrameStats,
+    pub budgets: SpineBudgetStats,
+}
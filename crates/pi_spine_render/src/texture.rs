@@ -0,0 +1,883 @@
+use std::sync::Arc;
+
+use bevy_ecs::prelude::Resource;
+use pi_hash::XHashMap;
+use pi_render::rhi::{asset::TextureRes, device::RenderDevice, RenderQueue, sampler::{SamplerDesc, EAddressMode, EFilterMode, EAnisotropyClamp}};
+
+/// 纹理上传选项
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SpineTextureDesc {
+    /// 在 CPU 上生成完整的 mip 链, 采样器使用三线性过滤
+    pub mipmap: bool,
+    /// 指定采样器, 如由 sampler_from_atlas_page 解析; None 时使用 SAMPLER_DESC 或 SAMPLER_DESC_MIPMAP
+    pub sampler: Option<SamplerDesc>,
+}
+
+fn atlas_filter(name: &str) -> (EFilterMode, EFilterMode) {
+    match name.trim() {
+        "Nearest" => (EFilterMode::Nearest, EFilterMode::Nearest),
+        "MipMapNearestNearest" => (EFilterMode::Nearest, EFilterMode::Nearest),
+        "MipMapLinearNearest" => (EFilterMode::Linear, EFilterMode::Nearest),
+        "MipMapNearestLinear" => (EFilterMode::Nearest, EFilterMode::Linear),
+        "MipMap" | "MipMapLinearLinear" => (EFilterMode::Linear, EFilterMode::Linear),
+        _ => (EFilterMode::Linear, EFilterMode::Nearest),
+    }
+}
+
+/// 由 Spine atlas 页头的 filter 与 repeat 行得到采样器, 如
+/// ```text
+/// filter: Nearest,Nearest
+/// repeat: none
+/// ```
+/// 未给出 repeat 时为 ClampToEdge, 未给出 filter 时为 Linear
+pub fn sampler_from_atlas_page(page: &str) -> SamplerDesc {
+    let mut result = SamplerDesc {
+        address_mode_u: EAddressMode::ClampToEdge,
+        address_mode_v: EAddressMode::ClampToEdge,
+        address_mode_w: EAddressMode::ClampToEdge,
+        mag_filter: EFilterMode::Linear,
+        min_filter: EFilterMode::Linear,
+        mipmap_filter: EFilterMode::Nearest,
+        compare: None,
+        anisotropy_clamp: EAnisotropyClamp::One,
+        border_color: None,
+    };
+    page.lines().for_each(|line| {
+        let (key, value) = match line.split_once(':') {
+            Some(v) => v,
+            None => return,
+        };
+        match key.trim() {
+            "filter" => {
+                let mut values = value.split(',');
+                let (min, mipmap) = atlas_filter(values.next().unwrap_or(""));
+                let (mag, _) = atlas_filter(values.next().unwrap_or(""));
+                result.min_filter = min;
+                result.mipmap_filter = mipmap;
+                result.mag_filter = mag;
+            },
+            "repeat" => {
+                let value = value.trim();
+                if value.contains('x') {
+                    result.address_mode_u = EAddressMode::Repeat;
+                }
+                if value.contains('y') {
+                    result.address_mode_v = EAddressMode::Repeat;
+                }
+            },
+            _ => {},
+        }
+    });
+    result
+}
+
+/// 完整 mip 链的层数
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+fn srgb_to_linear(v: u8) -> f32 {
+    let v = v as f32 / 255.;
+    if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(v: f32) -> u8 {
+    let v = if v <= 0.0031308 { v * 12.92 } else { 1.055 * v.powf(1. / 2.4) - 0.055 };
+    (v.clamp(0., 1.) * 255. + 0.5) as u8
+}
+
+/// Rgba8UnormSrgb 数据的下一级 mip: 2x2 盒式滤波, 颜色在线性空间平均, 奇数边取边缘像素
+pub fn downsample_rgba8_srgb(data: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
+    downsample_rgba8(data, width, height, true)
+}
+
+/// RGBA8 数据的下一级 mip; srgb 为 false 时按数值直接平均
+pub fn downsample_rgba8(data: &[u8], width: u32, height: u32, srgb: bool) -> (Vec<u8>, u32, u32) {
+    let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+    let table: Vec<f32> = (0..=255).map(|v| if srgb { srgb_to_linear(v) } else { v as f32 / 255. }).collect();
+    let mut result = Vec::with_capacity((w * h * 4) as usize);
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0f32; 4];
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let sx = (x * 2 + dx).min(width - 1);
+                let sy = (y * 2 + dy).min(height - 1);
+                let i = ((sy * width + sx) * 4) as usize;
+                sum[0] += table[data[i] as usize];
+                sum[1] += table[data[i + 1] as usize];
+                sum[2] += table[data[i + 2] as usize];
+                sum[3] += data[i + 3] as f32;
+            }
+            for c in &sum[0..3] {
+                result.push(if srgb { linear_to_srgb(c / 4.) } else { (c / 4. * 255. + 0.5) as u8 });
+            }
+            result.push((sum[3] / 4. + 0.5) as u8);
+        }
+    }
+    (result, w, h)
+}
+
+/// 品红与黑色相间的棋盘格 RGBA8 数据, 用作缺失纹理的占位
+pub fn checkerboard_rgba8(size: u32, cell: u32) -> Vec<u8> {
+    let cell = cell.max(1);
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            if (x / cell + y / cell) % 2 == 0 {
+                data.extend_from_slice(&[255, 0, 255, 255]);
+            } else {
+                data.extend_from_slice(&[0, 0, 0, 255]);
+            }
+        }
+    }
+    data
+}
+
+/// 创建 Rgba8UnormSrgb 纹理并写入数据, 按选项生成 mip 链
+pub fn create_texture_rgba8(
+    device: &RenderDevice,
+    queue: &RenderQueue,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    desc: &SpineTextureDesc,
+) -> (TextureRes, wgpu::Texture) {
+    create_texture_rgba8_with_format(device, queue, data, width, height, desc, true)
+}
+
+/// 创建 Rgba8UnormSrgb (srgb 为 true) 或 Rgba8Unorm 纹理并写入数据, 按选项生成 mip 链
+pub fn create_texture_rgba8_with_format(
+    device: &RenderDevice,
+    queue: &RenderQueue,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    desc: &SpineTextureDesc,
+    srgb: bool,
+) -> (TextureRes, wgpu::Texture) {
+    let format = if srgb { wgpu::TextureFormat::Rgba8UnormSrgb } else { wgpu::TextureFormat::Rgba8Unorm };
+    let mip_level_count = if desc.mipmap { mip_level_count(width, height) } else { 1 };
+    let texture = (**device).create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[format]
+    });
+
+    let mut size = 0;
+    let mut level = (data.to_vec(), width, height);
+    for mip_level in 0..mip_level_count {
+        if mip_level > 0 {
+            level = downsample_rgba8(&level.0, level.1, level.2, srgb);
+        }
+        let (data, width, height) = (&level.0, level.1, level.2);
+        size += data.len();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(4 * width).map(|r| {r.get()}),
+                rows_per_image: std::num::NonZeroU32::new(height).map(|r| {r.get()}),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: None,
+        format: Some(format),
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        aspect: wgpu::TextureAspect::All,
+        base_mip_level: 0,
+        mip_level_count: None,
+        base_array_layer: 0,
+        array_layer_count: None,
+    });
+
+    (TextureRes::new(width, height, size, texture_view, true, format), texture)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ESpineTextureError {
+    /// 不是 KTX2/DDS 或头部损坏
+    Container(&'static str),
+    /// 不支持的像素格式, KTX2 为 vkFormat, DDS 为 DXGI 格式或 FourCC
+    UnsupportedFormat(u32),
+    /// KTX2 的超压缩 (Basis/Zstd) 不支持
+    Supercompressed(u32),
+    /// 数据长度不足
+    Truncated,
+    /// CPU 解码失败
+    Decode(&'static str),
+    /// 更新区域超出纹理或数据长度不足
+    Region { x: u32, y: u32, width: u32, height: u32 },
+    /// 仅 Rgba8UnormSrgb 纹理支持局部更新
+    RegionFormat(wgpu::TextureFormat),
+    /// 纹理数组的层数为 0 或超出设备限制
+    ArrayLayers(u32),
+    /// 尺寸为 0 或超出上限
+    Size { width: u32, height: u32 },
+    /// mip 层数超出尺寸允许的层数
+    Levels(u32),
+}
+impl std::fmt::Display for ESpineTextureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Container(msg) => write!(f, "invalid texture container: {}", msg),
+            Self::UnsupportedFormat(format) => write!(f, "unsupported texture format {}", format),
+            Self::Supercompressed(scheme) => write!(f, "unsupported KTX2 supercompression {}", scheme),
+            Self::Truncated => write!(f, "texture data truncated"),
+            Self::Decode(msg) => write!(f, "texture decode failed: {}", msg),
+            Self::Region { x, y, width, height } => write!(f, "invalid texture region ({}, {}, {}, {})", x, y, width, height),
+            Self::RegionFormat(format) => write!(f, "region update unsupported for {:?}", format),
+            Self::ArrayLayers(layers) => write!(f, "invalid texture array layer count {}", layers),
+            Self::Size { width, height } => write!(f, "invalid texture size {}x{}", width, height),
+            Self::Levels(levels) => write!(f, "invalid mip level count {}", levels),
+        }
+    }
+}
+impl std::error::Error for ESpineTextureError {}
+
+/// 从 KTX2/DDS 中解析出的压缩纹理
+pub struct SpineCompressedTexture<'a> {
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    /// 各 mip 层数据, 从 0 层开始
+    pub levels: Vec<&'a [u8]>,
+}
+
+/// KTX2/DDS 尺寸的上限
+pub const MAX_COMPRESSED_SIZE: u32 = 16384;
+/// CPU 解码的像素数上限, 解码结果按此分配内存
+pub const MAX_DECODE_PIXELS: u64 = 4096 * 4096;
+
+/// 检查文件头中的尺寸与 mip 层数
+fn check_header(width: u32, height: u32, level_count: u32) -> Result<(), ESpineTextureError> {
+    if width == 0 || height == 0 || width > MAX_COMPRESSED_SIZE || height > MAX_COMPRESSED_SIZE {
+        return Err(ESpineTextureError::Size { width, height });
+    }
+    if level_count > mip_level_count(width, height) {
+        return Err(ESpineTextureError::Levels(level_count));
+    }
+    Ok(())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ESpineTextureError> {
+    data.get(offset..offset + 4).map(|v| u32::from_le_bytes([v[0], v[1], v[2], v[3]])).ok_or(ESpineTextureError::Truncated)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, ESpineTextureError> {
+    Ok(read_u32(data, offset)? as u64 | ((read_u32(data, offset + 4)? as u64) << 32))
+}
+
+fn astc(block: wgpu::AstcBlock, srgb: bool) -> wgpu::TextureFormat {
+    wgpu::TextureFormat::Astc { block, channel: if srgb { wgpu::AstcChannel::UnormSrgb } else { wgpu::AstcChannel::Unorm } }
+}
+
+fn vk_format(format: u32) -> Option<wgpu::TextureFormat> {
+    use wgpu::{TextureFormat as F, AstcBlock as B};
+    let srgb = format % 2 == 0;
+    let result = match format {
+        37 => F::Rgba8Unorm,
+        43 => F::Rgba8UnormSrgb,
+        137 => F::Bc3RgbaUnorm,
+        138 => F::Bc3RgbaUnormSrgb,
+        145 => F::Bc7RgbaUnorm,
+        146 => F::Bc7RgbaUnormSrgb,
+        147 => F::Etc2Rgb8Unorm,
+        148 => F::Etc2Rgb8UnormSrgb,
+        149 => F::Etc2Rgb8A1Unorm,
+        150 => F::Etc2Rgb8A1UnormSrgb,
+        151 => F::Etc2Rgba8Unorm,
+        152 => F::Etc2Rgba8UnormSrgb,
+        157 | 158 => astc(B::B4x4, srgb),
+        159 | 160 => astc(B::B5x4, srgb),
+        161 | 162 => astc(B::B5x5, srgb),
+        163 | 164 => astc(B::B6x5, srgb),
+        165 | 166 => astc(B::B6x6, srgb),
+        167 | 168 => astc(B::B8x5, srgb),
+        169 | 170 => astc(B::B8x6, srgb),
+        171 | 172 => astc(B::B8x8, srgb),
+        173 | 174 => astc(B::B10x5, srgb),
+        175 | 176 => astc(B::B10x6, srgb),
+        177 | 178 => astc(B::B10x8, srgb),
+        179 | 180 => astc(B::B10x10, srgb),
+        181 | 182 => astc(B::B12x10, srgb),
+        183 | 184 => astc(B::B12x12, srgb),
+        _ => return None,
+    };
+    Some(result)
+}
+
+pub const KTX2_IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+pub const DDS_MAGIC: [u8; 4] = *b"DDS ";
+
+/// 解析 KTX2, 仅取第一层数组与第一个面
+pub fn parse_ktx2(data: &[u8]) -> Result<SpineCompressedTexture, ESpineTextureError> {
+    if !data.starts_with(&KTX2_IDENTIFIER) {
+        return Err(ESpineTextureError::Container("KTX2 identifier"));
+    }
+    let vk = read_u32(data, 12)?;
+    let width = read_u32(data, 20)?;
+    let height = read_u32(data, 24)?.max(1);
+    let level_count = read_u32(data, 40)?.max(1);
+    let supercompression = read_u32(data, 44)?;
+    if supercompression != 0 {
+        return Err(ESpineTextureError::Supercompressed(supercompression));
+    }
+    let format = vk_format(vk).ok_or(ESpineTextureError::UnsupportedFormat(vk))?;
+    check_header(width, height, level_count)?;
+
+    let mut levels = vec![];
+    for level in 0..level_count as usize {
+        // 偏移与长度来自文件头, 溢出或短于该层应有的字节数视为截断
+        let offset = usize::try_from(read_u64(data, 80 + level * 24)?).map_err(|_| ESpineTextureError::Truncated)?;
+        let length = usize::try_from(read_u64(data, 80 + level * 24 + 8)?).map_err(|_| ESpineTextureError::Truncated)?;
+        let expected = level_bytes(format, mip_size(width, level as u32), mip_size(height, level as u32)).ok_or(ESpineTextureError::Truncated)?;
+        if length < expected {
+            return Err(ESpineTextureError::Truncated);
+        }
+        let end = offset.checked_add(expected).ok_or(ESpineTextureError::Truncated)?;
+        levels.push(data.get(offset..end).ok_or(ESpineTextureError::Truncated)?);
+    }
+
+    Ok(SpineCompressedTexture { format, width, height, levels })
+}
+
+/// 解析 DDS, 支持 DXT5 与 DX10 扩展头中的 BC3/BC7
+pub fn parse_dds(data: &[u8]) -> Result<SpineCompressedTexture, ESpineTextureError> {
+    use wgpu::TextureFormat as F;
+    if !data.starts_with(&DDS_MAGIC) || read_u32(data, 4)? != 124 {
+        return Err(ESpineTextureError::Container("DDS header"));
+    }
+    let height = read_u32(data, 12)?;
+    let width = read_u32(data, 16)?;
+    let level_count = read_u32(data, 28)?.max(1);
+    let fourcc = read_u32(data, 84)?;
+
+    let (format, mut offset) = if fourcc == u32::from_le_bytes(*b"DX10") {
+        let dxgi = read_u32(data, 128)?;
+        let format = match dxgi {
+            77 => F::Bc3RgbaUnorm,
+            78 => F::Bc3RgbaUnormSrgb,
+            98 => F::Bc7RgbaUnorm,
+            99 => F::Bc7RgbaUnormSrgb,
+            _ => return Err(ESpineTextureError::UnsupportedFormat(dxgi)),
+        };
+        (format, 148)
+    } else if fourcc == u32::from_le_bytes(*b"DXT5") {
+        (F::Bc3RgbaUnorm, 128)
+    } else {
+        return Err(ESpineTextureError::UnsupportedFormat(fourcc));
+    };
+    check_header(width, height, level_count)?;
+
+    let mut levels = vec![];
+    for level in 0..level_count {
+        let length = level_bytes(format, mip_size(width, level), mip_size(height, level)).ok_or(ESpineTextureError::Truncated)?;
+        let end = offset.checked_add(length).ok_or(ESpineTextureError::Truncated)?;
+        levels.push(data.get(offset..end).ok_or(ESpineTextureError::Truncated)?);
+        offset = end;
+    }
+
+    Ok(SpineCompressedTexture { format, width, height, levels })
+}
+
+/// 按文件头识别 KTX2 或 DDS
+pub fn parse_compressed(data: &[u8]) -> Result<SpineCompressedTexture, ESpineTextureError> {
+    if data.starts_with(&KTX2_IDENTIFIER) {
+        parse_ktx2(data)
+    } else if data.starts_with(&DDS_MAGIC) {
+        parse_dds(data)
+    } else {
+        Err(ESpineTextureError::Container("unknown"))
+    }
+}
+
+/// 尺寸来自文件头, 溢出时返回 None
+fn level_bytes(format: wgpu::TextureFormat, width: u32, height: u32) -> Option<usize> {
+    let (bw, bh) = format.block_dimensions();
+    let block = format.block_size(None).unwrap_or(4) as u64;
+    let blocks_x = (width as u64 + bw as u64 - 1) / bw as u64;
+    let blocks_y = (height as u64 + bh as u64 - 1) / bh as u64;
+    usize::try_from(blocks_x.checked_mul(blocks_y)?.checked_mul(block)?).ok()
+}
+
+/// 第 level 层 mip 的边长, 至少为 1
+fn mip_size(size: u32, level: u32) -> u32 {
+    size.checked_shr(level).unwrap_or(0).max(1)
+}
+
+/// CPU 解码第 0 层为 RGBA8
+pub fn decode_to_rgba8(texture: &SpineCompressedTexture) -> Result<Vec<u8>, ESpineTextureError> {
+    use wgpu::TextureFormat as F;
+    let data = texture.levels.first().ok_or(ESpineTextureError::Truncated)?;
+    if texture.width as u64 * texture.height as u64 > MAX_DECODE_PIXELS {
+        return Err(ESpineTextureError::Size { width: texture.width, height: texture.height });
+    }
+    let (width, height) = (texture.width as usize, texture.height as usize);
+    if matches!(texture.format, F::Rgba8Unorm | F::Rgba8UnormSrgb) {
+        return data.get(0..width * height * 4).map(|v| v.to_vec()).ok_or(ESpineTextureError::Truncated);
+    }
+
+    let mut image = vec![0u32; width * height];
+    let result = match texture.format {
+        F::Bc3RgbaUnorm | F::Bc3RgbaUnormSrgb => texture2ddecoder::decode_bc3(data, width, height, &mut image),
+        F::Bc7RgbaUnorm | F::Bc7RgbaUnormSrgb => texture2ddecoder::decode_bc7(data, width, height, &mut image),
+        F::Etc2Rgb8Unorm | F::Etc2Rgb8UnormSrgb => texture2ddecoder::decode_etc2_rgb(data, width, height, &mut image),
+        F::Etc2Rgb8A1Unorm | F::Etc2Rgb8A1UnormSrgb => texture2ddecoder::decode_etc2_rgba1(data, width, height, &mut image),
+        F::Etc2Rgba8Unorm | F::Etc2Rgba8UnormSrgb => texture2ddecoder::decode_etc2_rgba8(data, width, height, &mut image),
+        F::Astc { .. } => {
+            let (bw, bh) = texture.format.block_dimensions();
+            texture2ddecoder::decode_astc(data, width, height, bw as usize, bh as usize, &mut image)
+        },
+        _ => return Err(ESpineTextureError::Decode("format")),
+    };
+    result.map_err(ESpineTextureError::Decode)?;
+
+    // 解码结果为 0xAARRGGBB
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    image.iter().for_each(|pixel| {
+        let [b, g, r, a] = pixel.to_le_bytes();
+        rgba.extend_from_slice(&[r, g, b, a]);
+    });
+    Ok(rgba)
+}
+
+/// 设备支持该格式时直接上传各 mip 层, 否则在 CPU 上解码为 Rgba8UnormSrgb 或 Rgba8Unorm (与源格式一致);
+/// 同时返回实际上传的 mip 层数
+pub fn create_texture_compressed(
+    device: &RenderDevice,
+    queue: &RenderQueue,
+    data: &[u8],
+    desc: &SpineTextureDesc,
+) -> Result<(TextureRes, wgpu::Texture, u32), ESpineTextureError> {
+    let texture = parse_compressed(data)?;
+    let (width, height, format) = (texture.width, texture.height, texture.format);
+    let max = device.limits().max_texture_dimension_2d;
+    if width > max || height > max {
+        return Err(ESpineTextureError::Size { width, height });
+    }
+    let (bw, bh) = format.block_dimensions();
+    let supported = device.features().contains(format.required_features()) && width % bw == 0 && height % bh == 0;
+
+    if !supported {
+        let rgba = decode_to_rgba8(&texture)?;
+        let (textureres, texture) = create_texture_rgba8_with_format(device, queue, &rgba, width, height, desc, format.is_srgb());
+        let levels = if desc.mipmap { mip_level_count(width, height) } else { 1 };
+        return Ok((textureres, texture, levels));
+    }
+
+    let gpu_texture = (**device).create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: texture.levels.len() as u32,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[format]
+    });
+
+    let block = format.block_size(None).unwrap_or(4);
+    let mut size = 0;
+    for (mip_level, level) in texture.levels.iter().enumerate() {
+        let (w, h) = (mip_size(width, mip_level as u32), mip_size(height, mip_level as u32));
+        // 压缩格式按整块拷贝
+        let (physical_w, physical_h) = ((w + bw - 1) / bw * bw, (h + bh - 1) / bh * bh);
+        size += level.len();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &gpu_texture,
+                mip_level: mip_level as u32,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            level,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(physical_w / bw * block),
+                rows_per_image: Some(physical_h / bh),
+            },
+            wgpu::Extent3d {
+                width: physical_w,
+                height: physical_h,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    let texture_view = gpu_texture.create_view(&wgpu::TextureViewDescriptor {
+        label: None,
+        format: Some(format),
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        aspect: wgpu::TextureAspect::All,
+        base_mip_level: 0,
+        mip_level_count: None,
+        base_array_layer: 0,
+        array_layer_count: None,
+    });
+
+    Ok((TextureRes::new(width, height, size, texture_view, true, format), gpu_texture, texture.levels.len() as u32))
+}
+
+/// 保留纹理对象以便局部更新, 以纹理资源的 key 索引; 纹理资源仍在 AssetMgr 中 (含缓存) 或仍被渲染器引用时保留
+#[derive(Default, Resource)]
+pub struct SpineTextureStore {
+    textures: XHashMap<u64, Arc<wgpu::Texture>>,
+}
+impl SpineTextureStore {
+    pub fn insert(&mut self, key: u64, texture: Arc<wgpu::Texture>) {
+        self.textures.insert(key, texture);
+    }
+    pub fn remove(&mut self, key: u64) -> Option<Arc<wgpu::Texture>> {
+        self.textures.remove(&key)
+    }
+    pub fn get(&self, key: u64) -> Option<&Arc<wgpu::Texture>> {
+        self.textures.get(&key)
+    }
+    pub fn retain(&mut self, mut f: impl FnMut(u64) -> bool) {
+        self.textures.retain(|key, _| f(*key));
+    }
+}
+
+/// 将同尺寸的多页 RGBA8 数据创建为 Rgba8UnormSrgb 纹理数组 (D2Array 视图), 页序号即层序号
+pub fn create_texture_array_rgba8(
+    device: &RenderDevice,
+    queue: &RenderQueue,
+    pages: &[&[u8]],
+    width: u32,
+    height: u32,
+    desc: &SpineTextureDesc,
+) -> Result<(TextureRes, wgpu::Texture), ESpineTextureError> {
+    let layers = pages.len() as u32;
+    if layers == 0 || layers > device.limits().max_texture_array_layers {
+        return Err(ESpineTextureError::ArrayLayers(layers));
+    }
+    if pages.iter().any(|page| page.len() < (width * height * 4) as usize) {
+        return Err(ESpineTextureError::Truncated);
+    }
+
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let mip_level_count = if desc.mipmap { mip_level_count(width, height) } else { 1 };
+    let texture = (**device).create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: layers,
+        },
+        mip_level_count,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[format]
+    });
+
+    let mut size = 0;
+    pages.iter().enumerate().for_each(|(layer, page)| {
+        let mut level = (page[0..(width * height * 4) as usize].to_vec(), width, height);
+        for mip_level in 0..mip_level_count {
+            if mip_level > 0 {
+                level = downsample_rgba8_srgb(&level.0, level.1, level.2);
+            }
+            let (data, width, height) = (&level.0, level.1, level.2);
+            size += data.len();
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level,
+                    origin: wgpu::Origin3d { x: 0, y: 0, z: layer as u32 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(4 * width).map(|r| {r.get()}),
+                    rows_per_image: std::num::NonZeroU32::new(height).map(|r| {r.get()}),
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    });
+
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: None,
+        format: Some(format),
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        aspect: wgpu::TextureAspect::All,
+        base_mip_level: 0,
+        mip_level_count: None,
+        base_array_layer: 0,
+        array_layer_count: None,
+    });
+
+    Ok((TextureRes::new(width, height, size, texture_view, true, format), texture))
+}
+
+/// 将 RGBA8 数据写入 Rgba8UnormSrgb 纹理的 (x, y, width, height) 区域;
+/// 有 mip 链时同时写入缩小后的区域, 区域按 2 的幂对齐时各级结果精确
+pub fn write_texture_region(
+    queue: &RenderQueue,
+    texture: &wgpu::Texture,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Result<(), ESpineTextureError> {
+    if texture.format() != wgpu::TextureFormat::Rgba8UnormSrgb {
+        return Err(ESpineTextureError::RegionFormat(texture.format()));
+    }
+    if width == 0 || height == 0
+        || x.saturating_add(width) > texture.width() || y.saturating_add(height) > texture.height()
+        || data.len() < (width * height * 4) as usize
+    {
+        return Err(ESpineTextureError::Region { x, y, width, height });
+    }
+
+    let mut level = (data[0..(width * height * 4) as usize].to_vec(), width, height);
+    for mip_level in 0..texture.mip_level_count() {
+        if mip_level > 0 {
+            level = downsample_rgba8_srgb(&level.0, level.1, level.2);
+        }
+        let (data, width, height) = (&level.0, level.1, level.2);
+        // 该级的区域起点, 并裁剪到该级尺寸内
+        let level_width = (texture.width() >> mip_level).max(1);
+        let level_height = (texture.height() >> mip_level).max(1);
+        let origin_x = (x >> mip_level).min(level_width - 1);
+        let origin_y = (y >> mip_level).min(level_height - 1);
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level,
+                origin: wgpu::Origin3d { x: origin_x, y: origin_y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(4 * width).map(|r| {r.get()}),
+                rows_per_image: std::num::NonZeroU32::new(height).map(|r| {r.get()}),
+            },
+            wgpu::Extent3d {
+                width: width.min(level_width - origin_x),
+                height: height.min(level_height - origin_y),
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_compressed, parse_dds, parse_ktx2, decode_to_rgba8, ESpineTextureError, SpineCompressedTexture, KTX2_IDENTIFIER, DDS_MAGIC, MAX_COMPRESSED_SIZE};
+
+    const VK_BC7: u32 = 145;
+
+    fn put(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put64(data: &mut [u8], offset: usize, value: u64) {
+        data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// KTX2 文件头, 各层 (偏移, 长度) 写入层索引, 数据区补零到 total
+    fn ktx2(vk: u32, width: u32, height: u32, level_count: u32, levels: &[(u64, u64)], total: usize) -> Vec<u8> {
+        let mut data = vec![0u8; total.max(80 + levels.len() * 24)];
+        data[0..12].copy_from_slice(&KTX2_IDENTIFIER);
+        put(&mut data, 12, vk);
+        put(&mut data, 20, width);
+        put(&mut data, 24, height);
+        put(&mut data, 40, level_count);
+        levels.iter().enumerate().for_each(|(level, (offset, length))| {
+            put64(&mut data, 80 + level * 24, *offset);
+            put64(&mut data, 80 + level * 24 + 8, *length);
+        });
+        data
+    }
+
+    /// 8x8 BC7, 两层 (64 + 16 字节)
+    fn ktx2_bc7() -> Vec<u8> {
+        ktx2(VK_BC7, 8, 8, 2, &[(128, 64), (192, 16)], 208)
+    }
+
+    /// DDS 文件头, fourcc 为 DX10 时追加扩展头, 数据区为 payload 个零字节
+    fn dds(fourcc: &[u8; 4], dxgi: u32, width: u32, height: u32, level_count: u32, payload: usize) -> Vec<u8> {
+        let header = if fourcc == b"DX10" { 148 } else { 128 };
+        let mut data = vec![0u8; header + payload];
+        data[0..4].copy_from_slice(&DDS_MAGIC);
+        put(&mut data, 4, 124);
+        put(&mut data, 12, height);
+        put(&mut data, 16, width);
+        put(&mut data, 28, level_count);
+        data[84..88].copy_from_slice(fourcc);
+        if header == 148 {
+            put(&mut data, 128, dxgi);
+        }
+        data
+    }
+
+    #[test]
+    fn ktx2_levels() {
+        let data = ktx2_bc7();
+        let texture = parse_ktx2(&data).ok().unwrap();
+        assert_eq!(texture.format, wgpu::TextureFormat::Bc7RgbaUnorm);
+        assert_eq!((texture.width, texture.height), (8, 8));
+        assert_eq!(texture.levels.iter().map(|v| v.len()).collect::<Vec<_>>(), vec![64, 16]);
+    }
+
+    #[test]
+    fn ktx2_bad_identifier() {
+        let mut data = ktx2_bc7();
+        data[1] = 0;
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Container("KTX2 identifier")));
+    }
+
+    #[test]
+    fn ktx2_truncated_header() {
+        let data = ktx2_bc7();
+        assert_eq!(parse_ktx2(&data[..30]).err(), Some(ESpineTextureError::Truncated));
+        // 层索引不完整
+        assert_eq!(parse_ktx2(&data[..100]).err(), Some(ESpineTextureError::Truncated));
+    }
+
+    #[test]
+    fn ktx2_unsupported() {
+        let data = ktx2(999, 8, 8, 1, &[(104, 64)], 168);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::UnsupportedFormat(999)));
+
+        let mut data = ktx2_bc7();
+        put(&mut data, 44, 1);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Supercompressed(1)));
+    }
+
+    #[test]
+    fn ktx2_size() {
+        let data = ktx2(VK_BC7, 0, 8, 1, &[(104, 64)], 168);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Size { width: 0, height: 8 }));
+
+        let data = ktx2(VK_BC7, MAX_COMPRESSED_SIZE + 1, 8, 1, &[(104, 64)], 168);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Size { width: MAX_COMPRESSED_SIZE + 1, height: 8 }));
+    }
+
+    #[test]
+    fn ktx2_excess_levels() {
+        // 8x8 最多 4 层
+        let data = ktx2(VK_BC7, 8, 8, 5, &[(0, 0); 5], 256);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Levels(5)));
+    }
+
+    #[test]
+    fn ktx2_level_ranges() {
+        // 长度短于该层应有的字节数
+        let data = ktx2(VK_BC7, 8, 8, 1, &[(104, 32)], 168);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Truncated));
+        // 偏移超出数据
+        let data = ktx2(VK_BC7, 8, 8, 1, &[(160, 64)], 168);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Truncated));
+        // 偏移加长度溢出
+        let data = ktx2(VK_BC7, 8, 8, 1, &[(u64::MAX - 8, 64)], 168);
+        assert_eq!(parse_ktx2(&data).err(), Some(ESpineTextureError::Truncated));
+        // 长度过大不影响, 只取该层应有的字节数
+        let data = ktx2(VK_BC7, 8, 8, 1, &[(104, u64::MAX)], 168);
+        assert_eq!(parse_ktx2(&data).ok().map(|v| v.levels[0].len()), Some(64));
+    }
+
+    #[test]
+    fn dds_levels() {
+        let data = dds(b"DXT5", 0, 8, 8, 2, 80);
+        let texture = parse_dds(&data).ok().unwrap();
+        assert_eq!(texture.format, wgpu::TextureFormat::Bc3RgbaUnorm);
+        assert_eq!(texture.levels.iter().map(|v| v.len()).collect::<Vec<_>>(), vec![64, 16]);
+
+        let data = dds(b"DX10", 99, 8, 4, 1, 32);
+        let texture = parse_dds(&data).ok().unwrap();
+        assert_eq!(texture.format, wgpu::TextureFormat::Bc7RgbaUnormSrgb);
+        assert_eq!((texture.width, texture.height), (8, 4));
+        assert_eq!(texture.levels[0].len(), 32);
+    }
+
+    #[test]
+    fn dds_malformed() {
+        let mut data = dds(b"DXT5", 0, 8, 8, 1, 64);
+        put(&mut data, 4, 100);
+        assert_eq!(parse_dds(&data).err(), Some(ESpineTextureError::Container("DDS header")));
+
+        let data = dds(b"DXT5", 0, 8, 8, 1, 64);
+        assert_eq!(parse_dds(&data[..60]).err(), Some(ESpineTextureError::Truncated));
+        // DX10 扩展头不完整
+        let data = dds(b"DX10", 98, 8, 8, 1, 0);
+        assert_eq!(parse_dds(&data[..130]).err(), Some(ESpineTextureError::Truncated));
+    }
+
+    #[test]
+    fn dds_unsupported() {
+        let data = dds(b"DXT1", 0, 8, 8, 1, 64);
+        assert_eq!(parse_dds(&data).err(), Some(ESpineTextureError::UnsupportedFormat(u32::from_le_bytes(*b"DXT1"))));
+
+        let data = dds(b"DX10", 28, 8, 8, 1, 256);
+        assert_eq!(parse_dds(&data).err(), Some(ESpineTextureError::UnsupportedFormat(28)));
+    }
+
+    #[test]
+    fn dds_header_checks() {
+        let data = dds(b"DXT5", 0, 8, 0, 1, 64);
+        assert_eq!(parse_dds(&data).err(), Some(ESpineTextureError::Size { width: 8, height: 0 }));
+
+        let data = dds(b"DXT5", 0, 8, 8, 6, 256);
+        assert_eq!(parse_dds(&data).err(), Some(ESpineTextureError::Levels(6)));
+
+        // 第二层数据不足
+        let data = dds(b"DXT5", 0, 8, 8, 2, 70);
+        assert_eq!(parse_dds(&data).err(), Some(ESpineTextureError::Truncated));
+    }
+
+    #[test]
+    fn compressed_dispatch() {
+        assert!(parse_compressed(&ktx2_bc7()).is_ok());
+        assert!(parse_compressed(&dds(b"DXT5", 0, 8, 8, 1, 64)).is_ok());
+        assert_eq!(parse_compressed(b"\x89PNG\r\n\x1a\n").err(), Some(ESpineTextureError::Container("unknown")));
+        assert_eq!(parse_compressed(&[]).err(), Some(ESpineTextureError::Container("unknown")));
+    }
+
+    #[test]
+    fn decode_pixel_limit() {
+        let texture = SpineCompressedTexture { format: wgpu::TextureFormat::Rgba8Unorm, width: 4097, height: 4096, levels: vec![&[][..]] };
+        assert_eq!(decode_to_rgba8(&texture), Err(ESpineTextureError::Size { width: 4097, height: 4096 }));
+
+        let data = [7u8; 16];
+        let texture = SpineCompressedTexture { format: wgpu::TextureFormat::Rgba8Unorm, width: 2, height: 2, levels: vec![&data[..]] };
+        assert_eq!(decode_to_rgba8(&texture), Ok(data.to_vec()));
+    }
+}
@@ -1,220 +1,338 @@
-
-use std::sync::Arc;
-
-use pi_render::{renderer::vertex_buffer::{ EVertexBufferRange, KeyVertexBuffer, VertexBufferAllocator, NotUpdatableBufferRange}, rhi::{device::RenderDevice, RenderQueue}};
-
-pub struct InstanceCacheBuffer {
-    vertices: Vec<u8>,
-    buffer: (Arc<NotUpdatableBufferRange>, u32, u32),
-}
-
-pub struct SpineVertexBufferAllocator {
-    list: Vec<InstanceCacheBuffer>,
-    used_index: usize,
-    /// 单个 Mesh 的实例化最多使用多少字节数据
-    /// 当 运行时超过该数据时 对数据进行截取
-    one_mesh_max_instance_bytes: u32,
-}
-impl SpineVertexBufferAllocator {
-    pub fn new(one_mesh_max_instance_bytes: u32) -> Self {
-        Self {
-            list: vec![],
-            used_index: 0,
-            one_mesh_max_instance_bytes,
-        }
-    }
-    pub fn instance_initial_buffer(&self) -> (Arc<NotUpdatableBufferRange>, u32, u32) {
-        (self.list[0].buffer.0.clone(), 0, 0)
-    }
-    /// 默认都是 f32
-    pub fn collect(&mut self, data: &[u8], bytes_per_instance: u32, allocator: &mut VertexBufferAllocator, device: &RenderDevice, queue: &RenderQueue) -> Option<(Arc<NotUpdatableBufferRange>, u32, u32)> {
-        let max_count = self.one_mesh_max_instance_bytes / bytes_per_instance;
-        let byte_size = data.len().min((max_count * bytes_per_instance) as usize);
-        let bytes = &data[0..byte_size];
-
-        if let Some(buffer) = self.list.get(self.used_index) {
-            if buffer.vertices.len() + bytes.len() > buffer.buffer.2 as usize {
-                self.used_index += 1;
-            }
-        };
-        if let Some(buffer) = self.list.get_mut(self.used_index)  {
-            let start = buffer.vertices.len();
-            bytes.iter().for_each(|v| { buffer.vertices.push(*v) });
-            return Some((buffer.buffer.0.clone(), start as u32, buffer.vertices.len() as u32));
-        } else {
-            let mut data = Vec::with_capacity(self.one_mesh_max_instance_bytes as usize);
-            bytes.iter().for_each(|v| { data.push(*v) });
-            let vertices = data.clone();
-
-            for _ in byte_size..self.one_mesh_max_instance_bytes as usize {
-                data.push(0);
-            }
-            if let Some(buffer) = allocator.create_not_updatable_buffer_pre(device, queue, &data, None) {
-                self.list.push(InstanceCacheBuffer {
-                    vertices,
-                    buffer: (buffer, 0, self.one_mesh_max_instance_bytes as u32),
-                    // key: KeyVertexBuffer::from(self.used_index.to_string().as_str()),
-                });
-                let buffer = self.list.get_mut(self.used_index).unwrap();
-                return Some(
-                    (buffer.buffer.0.clone(), 0, buffer.vertices.len() as u32)
-                );
-            } else {
-                return None;
-            }
-        };
-    }
-    pub fn upload(&mut self, queue: &RenderQueue) {
-        for idx in 0..(self.used_index + 1) {
-            if let Some(buffer) = self.list.get_mut(idx) {
-                if buffer.vertices.len() > 0 {
-                    queue.write_buffer(buffer.buffer.0.buffer(), 0, &buffer.vertices);
-                }
-                buffer.vertices.clear();
-            }
-        }
-        self.used_index = 0;
-    }
-}
-
-pub struct SpineIndicesBufferAllocator {
-    list: Vec<InstanceCacheBuffer>,
-    used_index: usize,
-    /// 单个 Mesh 的实例化最多使用多少字节数据
-    /// 当 运行时超过该数据时 对数据进行截取
-    one_mesh_max_instance_bytes: u32,
-}
-impl SpineIndicesBufferAllocator {
-    pub fn new(one_mesh_max_instance_bytes: u32) -> Self {
-        Self {
-            list: vec![],
-            used_index: 0,
-            one_mesh_max_instance_bytes,
-        }
-    }
-    pub fn instance_initial_buffer(&self) -> (Arc<NotUpdatableBufferRange>, u32, u32) {
-        (self.list[0].buffer.0.clone(), 0, 0)
-    }
-    /// 默认都是 f32
-    pub fn collect(&mut self, data: &[u8], bytes_per_instance: u32, allocator: &mut VertexBufferAllocator, device: &RenderDevice, queue: &RenderQueue) -> Option<(Arc<NotUpdatableBufferRange>, u32, u32)> {
-        let max_count = self.one_mesh_max_instance_bytes / bytes_per_instance;
-        let byte_size = data.len().min((max_count * bytes_per_instance) as usize);
-        let bytes = &data[0..byte_size];
-
-        if let Some(buffer) = self.list.get(self.used_index) {
-            if buffer.vertices.len() + bytes.len() > buffer.buffer.2 as usize {
-                self.used_index += 1;
-            }
-        };
-        if let Some(buffer) = self.list.get_mut(self.used_index)  {
-            let start = buffer.vertices.len();
-            bytes.iter().for_each(|v| { buffer.vertices.push(*v) });
-            return Some((buffer.buffer.0.clone(), start as u32, buffer.vertices.len() as u32));
-        } else {
-            let mut data = Vec::with_capacity(self.one_mesh_max_instance_bytes as usize);
-            bytes.iter().for_each(|v| { data.push(*v) });
-            let vertices = data.clone();
-
-            for _ in byte_size..self.one_mesh_max_instance_bytes as usize {
-                data.push(0);
-            }
-            if let Some(buffer) = allocator.create_not_updatable_buffer_for_index(device, queue, &data) {
-                match buffer {
-                    EVertexBufferRange::Updatable(_, _, _) => { return None; },
-                    EVertexBufferRange::NotUpdatable(buffer, _, _) => {
-                        self.list.push(InstanceCacheBuffer {
-                            vertices,
-                            buffer: (buffer, 0, self.one_mesh_max_instance_bytes as u32),
-                        });
-                        let buffer = self.list.get_mut(self.used_index).unwrap();
-                        return Some(
-                            (buffer.buffer.0.clone(), 0, buffer.vertices.len() as u32)
-                        );
-                    },
-                }
-            } else {
-                return None;
-            }
-        };
-    }
-    pub fn upload(&mut self, queue: &RenderQueue) {
-        for idx in 0..(self.used_index + 1) {
-            if let Some(buffer) = self.list.get_mut(idx) {
-                let count = buffer.vertices.len() / 4;
-                if count * 4 != buffer.vertices.len() {
-                    let diff = (count + 1) * 4 - buffer.vertices.len();
-                    for _ in 0..diff {
-                        buffer.vertices.push(0);
-                    }
-                };
-
-                if buffer.vertices.len() > 0 {
-                    queue.write_buffer(buffer.buffer.0.buffer(), 0, &buffer.vertices);
-                }
-                buffer.vertices.clear();
-            }
-        }
-        self.used_index = 0;
-    }
-}
-
-
-// pub struct SpineVertexBufferAllocator;
-// impl SpineVertexBufferAllocator {
-//     pub const MAX_VERTICES: u32 = 10920;
-//     pub fn init() -> Self {
-//         Self
-//     }
-//     pub fn colored(
-//         &mut self,
-//         vb_allocator: &mut VertexBufferAllocator,
-//         data: &[f32],
-//         device: &RenderDevice,
-//         queue: &RenderQueue,
-//     ) -> Option<EVertexBufferRange> {
-//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
-//             Some(range)
-//         } else {
-//             None
-//         }
-//     }
-//     pub fn colored_textured(
-//         &mut self,
-//         vb_allocator: &mut VertexBufferAllocator,
-//         data: &[f32],
-//         device: &RenderDevice,
-//         queue: &RenderQueue,
-//     ) -> Option<EVertexBufferRange> {
-//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
-//             Some(range)
-//         } else {
-//             None
-//         }
-//     }
-//     pub fn two_colored_textured(
-//         &mut self,
-//         vb_allocator: &mut VertexBufferAllocator,
-//         data: &[f32],
-//         device: &RenderDevice,
-//         queue: &RenderQueue,
-//     ) -> Option<EVertexBufferRange> {
-//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
-//             Some(range)
-//         } else {
-//             None
-//         }
-//     }
-//     pub fn indices(
-//         &mut self,
-//         vb_allocator: &mut VertexBufferAllocator,
-//         data: &[u16],
-//         device: &RenderDevice,
-//         queue: &RenderQueue,
-//     ) -> Option<EVertexBufferRange> {
-//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
-//             Some(range)
-//         } else {
-//             None
-//         }
-//     }
-// }
\ No newline at end of file
+
+use std::sync::Arc;
+
+use pi_render::{renderer::vertex_buffer::{ EVertexBufferRange, VertexBufferAllocator, NotUpdatableBufferRange}, rhi::{device::RenderDevice, RenderQueue, BufferInitDescriptor, buffer::Buffer}};
+
+pub struct InstanceCacheBuffer<B = Arc<NotUpdatableBufferRange>> {
+    vertices: Vec<u8>,
+    buffer: (B, u32, u32),
+}
+
+/// 按块管理的 Buffer 池, 块在帧之间复用
+/// 当前块放不下时写入下一块; 数据大于块大小时按数据大小创建块, 不截取数据
+struct SpineBufferArena<B = Arc<NotUpdatableBufferRange>> {
+    list: Vec<InstanceCacheBuffer<B>>,
+    used_index: usize,
+    /// 新建块的默认字节数
+    block_bytes: u32,
+}
+impl<B: Clone> SpineBufferArena<B> {
+    /// 写入偏移对齐, 满足 COPY_BUFFER_ALIGNMENT 及 Uint16/Uint32 索引对齐
+    const ALIGNMENT: u32 = 4;
+    fn new(block_bytes: u32) -> Self {
+        Self {
+            list: vec![],
+            used_index: 0,
+            block_bytes: Self::align(block_bytes.max(Self::ALIGNMENT)),
+        }
+    }
+    fn align(size: u32) -> u32 {
+        (size + Self::ALIGNMENT - 1) / Self::ALIGNMENT * Self::ALIGNMENT
+    }
+    fn instance_initial_buffer(&self) -> (B, u32, u32) {
+        (self.list[0].buffer.0.clone(), 0, 0)
+    }
+    fn collect(&mut self, data: &[u8], create: &mut dyn FnMut(&[u8]) -> Option<B>) -> Option<(B, u32, u32)> {
+        let byte_size = data.len() as u32;
+        loop {
+            let capacity = self.block_bytes.max(Self::align(byte_size));
+            if let Some(buffer) = self.list.get_mut(self.used_index) {
+                let start = Self::align(buffer.vertices.len() as u32);
+                if start + byte_size <= buffer.buffer.2 {
+                    buffer.vertices.resize(start as usize, 0);
+                    buffer.vertices.extend_from_slice(data);
+                    return Some((buffer.buffer.0.clone(), start, start + byte_size));
+                } else if buffer.vertices.len() == 0 {
+                    // 空块也放不下, 以更大的块替换
+                    let range = create(&vec![0; capacity as usize])?;
+                    *buffer = InstanceCacheBuffer { vertices: Vec::with_capacity(capacity as usize), buffer: (range, 0, capacity) };
+                } else {
+                    self.used_index += 1;
+                }
+            } else {
+                let range = create(&vec![0; capacity as usize])?;
+                self.list.push(InstanceCacheBuffer { vertices: Vec::with_capacity(capacity as usize), buffer: (range, 0, capacity) });
+            }
+        }
+    }
+    /// 将本帧写入的数据交给 write 上传, 之后各块从头复用
+    fn flush(&mut self, write: &mut dyn FnMut(&B, &[u8])) {
+        for idx in 0..(self.used_index + 1) {
+            if let Some(buffer) = self.list.get_mut(idx) {
+                let size = Self::align(buffer.vertices.len() as u32) as usize;
+                buffer.vertices.resize(size, 0);
+
+                if buffer.vertices.len() > 0 {
+                    write(&buffer.buffer.0, &buffer.vertices);
+                }
+                buffer.vertices.clear();
+            }
+        }
+        self.used_index = 0;
+    }
+    fn capacity(&self) -> usize {
+        self.list.iter().map(|buffer| buffer.buffer.2 as usize).sum()
+    }
+    fn used(&self) -> usize {
+        self.list.iter().take(self.used_index + 1).map(|buffer| buffer.vertices.len()).sum()
+    }
+}
+impl SpineBufferArena {
+    fn upload(&mut self, queue: &RenderQueue) {
+        self.flush(&mut |buffer, data| {
+            queue.write_buffer(buffer.buffer(), 0, data);
+        });
+    }
+}
+
+pub struct SpineVertexBufferAllocator {
+    arena: SpineBufferArena,
+}
+impl SpineVertexBufferAllocator {
+    pub fn new(block_bytes: u32) -> Self {
+        Self { arena: SpineBufferArena::new(block_bytes) }
+    }
+    pub fn instance_initial_buffer(&self) -> (Arc<NotUpdatableBufferRange>, u32, u32) {
+        self.arena.instance_initial_buffer()
+    }
+    /// 写入一次绘制的顶点字节, 布局由管线的顶点压缩方式决定
+    pub fn collect(&mut self, data: &[u8], allocator: &mut VertexBufferAllocator, device: &RenderDevice, queue: &RenderQueue) -> Option<(Arc<NotUpdatableBufferRange>, u32, u32)> {
+        self.arena.collect(data, &mut |data| {
+            allocator.create_not_updatable_buffer_pre(device, queue, data, None)
+        })
+    }
+    pub fn upload(&mut self, queue: &RenderQueue) {
+        self.arena.upload(queue);
+    }
+    /// 已创建的块的总字节数
+    pub fn capacity(&self) -> usize {
+        self.arena.capacity()
+    }
+    /// 本帧已写入 (尚未上传) 的字节数
+    pub fn used(&self) -> usize {
+        self.arena.used()
+    }
+}
+
+pub struct SpineIndicesBufferAllocator {
+    arena: SpineBufferArena,
+}
+impl SpineIndicesBufferAllocator {
+    pub fn new(block_bytes: u32) -> Self {
+        Self { arena: SpineBufferArena::new(block_bytes) }
+    }
+    pub fn instance_initial_buffer(&self) -> (Arc<NotUpdatableBufferRange>, u32, u32) {
+        self.arena.instance_initial_buffer()
+    }
+    pub fn collect(&mut self, data: &[u8], allocator: &mut VertexBufferAllocator, device: &RenderDevice, queue: &RenderQueue) -> Option<(Arc<NotUpdatableBufferRange>, u32, u32)> {
+        self.arena.collect(data, &mut |data| {
+            match allocator.create_not_updatable_buffer_for_index(device, queue, data) {
+                Some(EVertexBufferRange::NotUpdatable(buffer, _, _)) => Some(buffer),
+                _ => None,
+            }
+        })
+    }
+    pub fn upload(&mut self, queue: &RenderQueue) {
+        self.arena.upload(queue);
+    }
+    /// 已创建的块的总字节数
+    pub fn capacity(&self) -> usize {
+        self.arena.capacity()
+    }
+    /// 本帧已写入 (尚未上传) 的字节数
+    pub fn used(&self) -> usize {
+        self.arena.used()
+    }
+}
+
+
+// pub struct SpineVertexBufferAllocator;
+// impl SpineVertexBufferAllocator {
+//     pub const MAX_VERTICES: u32 = 10920;
+//     pub fn init() -> Self {
+//         Self
+//     }
+//     pub fn colored(
+//         &mut self,
+//         vb_allocator: &mut VertexBufferAllocator,
+//         data: &[f32],
+//         device: &RenderDevice,
+//         queue: &RenderQueue,
+//     ) -> Option<EVertexBufferRange> {
+//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
+//             Some(range)
+//         } else {
+//             None
+//         }
+//     }
+//     pub fn colored_textured(
+//         &mut self,
+//         vb_allocator: &mut VertexBufferAllocator,
+//         data: &[f32],
+//         device: &RenderDevice,
+//         queue: &RenderQueue,
+//     ) -> Option<EVertexBufferRange> {
+//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
+//             Some(range)
+//         } else {
+//             None
+//         }
+//     }
+//     pub fn two_colored_textured(
+//         &mut self,
+//         vb_allocator: &mut VertexBufferAllocator,
+//         data: &[f32],
+//         device: &RenderDevice,
+//         queue: &RenderQueue,
+//     ) -> Option<EVertexBufferRange> {
+//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
+//             Some(range)
+//         } else {
+//             None
+//         }
+//     }
+//     pub fn indices(
+//         &mut self,
+//         vb_allocator: &mut VertexBufferAllocator,
+//         data: &[u16],
+//         device: &RenderDevice,
+//         queue: &RenderQueue,
+//     ) -> Option<EVertexBufferRange> {
+//         if let Some(range) = vb_allocator.create_not_updatable_buffer(device, queue, bytemuck::cast_slice(data)) {
+//             Some(range)
+//         } else {
+//             None
+//         }
+//     }
+// }
+
+/// 常驻的网格顶点与索引 Buffer, 创建后不再逐帧上传; 用于 GPU 蒙皮及内容未变的保留绘制.
+/// GPU 蒙皮的顶点布局见 KeySpineShader::Skinned: 骨骼序号 x4, 权重 x4, 骨骼空间坐标 x4 (vec2), uv, 颜色, 暗色
+pub struct SpineMesh {
+    pub(crate) vertices: Buffer,
+    pub(crate) indices: Buffer,
+    pub(crate) vertex_count: u32,
+    pub(crate) index_count: u32,
+}
+impl SpineMesh {
+    pub fn new(device: &RenderDevice, vertices: &[f32], indices: &[u16], vertex_bytes: u32) -> Self {
+        let vertices: &[u8] = bytemuck::cast_slice(vertices);
+        Self::with_bytes(device, vertices, indices, vertices.len() as u32 / vertex_bytes)
+    }
+    /// 以已打包的顶点字节创建
+    pub fn with_bytes(device: &RenderDevice, vertices: &[u8], indices: &[u16], vertex_count: u32) -> Self {
+        let mut vertices_bytes = vertices.to_vec();
+        let mut indices_bytes: Vec<u8> = bytemuck::cast_slice(indices).to_vec();
+        // Buffer 大小须按 COPY_BUFFER_ALIGNMENT 对齐
+        vertices_bytes.resize((vertices_bytes.len() + 3) / 4 * 4, 0);
+        indices_bytes.resize((indices_bytes.len() + 3) / 4 * 4, 0);
+        Self {
+            vertices: device.create_buffer_with_data(
+                &BufferInitDescriptor {
+                    label: Some("SpineMeshVertices"),
+                    contents: &vertices_bytes,
+                    usage: wgpu::BufferUsages::VERTEX,
+                }
+            ),
+            indices: device.create_buffer_with_data(
+                &BufferInitDescriptor {
+                    label: Some("SpineMeshIndices"),
+                    contents: &indices_bytes,
+                    usage: wgpu::BufferUsages::INDEX,
+                }
+            ),
+            vertex_count,
+            index_count: indices.len() as u32,
+        }
+    }
+    /// 占用的 GPU 字节数
+    pub fn bytes(&self) -> u64 {
+        self.vertices.size() + self.indices.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpineBufferArena;
+
+    /// 以递增编号代替 GPU Buffer, 记录创建次数
+    fn collect(arena: &mut SpineBufferArena<u32>, data: &[u8], created: &mut u32) -> Option<(u32, u32, u32)> {
+        arena.collect(data, &mut |_| {
+            *created += 1;
+            Some(*created)
+        })
+    }
+
+    #[test]
+    fn offsets_are_aligned() {
+        let mut arena = SpineBufferArena::<u32>::new(64);
+        let mut created = 0;
+        assert_eq!(collect(&mut arena, &[1; 6], &mut created), Some((1, 0, 6)));
+        assert_eq!(collect(&mut arena, &[2; 6], &mut created), Some((1, 8, 14)));
+        assert_eq!(created, 1);
+    }
+
+    #[test]
+    fn full_block_moves_to_next() {
+        let mut arena = SpineBufferArena::<u32>::new(64);
+        let mut created = 0;
+        assert_eq!(collect(&mut arena, &[0; 40], &mut created), Some((1, 0, 40)));
+        assert_eq!(collect(&mut arena, &[0; 40], &mut created), Some((2, 0, 40)));
+        assert_eq!(arena.capacity(), 128);
+        assert_eq!(arena.used(), 80);
+    }
+
+    #[test]
+    fn oversized_data_gets_own_block() {
+        let mut arena = SpineBufferArena::<u32>::new(64);
+        let mut created = 0;
+        assert_eq!(collect(&mut arena, &[0; 10], &mut created), Some((1, 0, 10)));
+        assert_eq!(collect(&mut arena, &[0; 101], &mut created), Some((2, 0, 101)));
+        assert_eq!(arena.capacity(), 64 + 104);
+    }
+
+    #[test]
+    fn empty_block_is_replaced() {
+        let mut arena = SpineBufferArena::<u32>::new(64);
+        let mut created = 0;
+        collect(&mut arena, &[0; 16], &mut created);
+        arena.flush(&mut |_, _| {});
+        // 复用的首块为空但放不下, 以更大的块替换而不是追加
+        assert_eq!(collect(&mut arena, &[0; 200], &mut created), Some((2, 0, 200)));
+        assert_eq!(arena.list.len(), 1);
+        assert_eq!(arena.capacity(), 200);
+    }
+
+    #[test]
+    fn blocks_are_reused_after_flush() {
+        let mut arena = SpineBufferArena::<u32>::new(64);
+        let mut created = 0;
+        collect(&mut arena, &[0; 40], &mut created);
+        collect(&mut arena, &[0; 40], &mut created);
+        let mut writes = vec![];
+        arena.flush(&mut |buffer, data| writes.push((*buffer, data.len())));
+        assert_eq!(writes, vec![(1, 40), (2, 40)]);
+        assert_eq!(arena.used(), 0);
+
+        assert_eq!(collect(&mut arena, &[0; 40], &mut created), Some((1, 0, 40)));
+        assert_eq!(collect(&mut arena, &[0; 40], &mut created), Some((2, 0, 40)));
+        assert_eq!(created, 2);
+    }
+
+    #[test]
+    fn flush_pads_to_alignment() {
+        let mut arena = SpineBufferArena::<u32>::new(64);
+        let mut created = 0;
+        collect(&mut arena, &[0; 7], &mut created);
+        let mut writes = vec![];
+        arena.flush(&mut |_, data| writes.push(data.len()));
+        assert_eq!(writes, vec![8]);
+    }
+
+    #[test]
+    fn create_failure_returns_none() {
+        let mut arena = SpineBufferArena::<u32>::new(64);
+        assert_eq!(arena.collect(&[0; 4], &mut |_| None), None);
+        assert_eq!(arena.capacity(), 0);
+    }
+}
@@ -1,4 +1,4 @@
-// pub mod renderer;
-pub mod vertices;
-pub mod indices;
+// pub mod renderer;
+pub mod vertices;
+pub mod indices;
 pub mod renderer_bevy;
\ No newline at end of file
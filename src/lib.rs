@@ -1,3 +1,3 @@
-pub use pi_spine_render::*;
-
+pub use pi_spine_render::*;
+
 pub mod render;
\ No newline at end of file